@@ -1,12 +1,25 @@
+use evoground_core::benchmarks::{Benchmark, Rastrigin};
 use evoground_core::evolution_strategies::*;
+use evoground_core::optimizer::Optimizer;
+use evoground_core::termination::MaxGenerations;
 
 fn main() {
-    let mutator = SimpleMutator::new(0.1, 0.5); // Example mutation parameters
-    let objective = |x: f64| -> f64 { -(x - 2.0).powi(2) + 10.0 }; // Example objective function
-    let initial_value = 0.5; // Example initial value
+    let benchmark = Rastrigin; // Example benchmark function
+    let dimension = 5;
+    let initial_value = vec![2.5; dimension]; // Example starting point
 
-    let mut strategy = OnePlusOneStrategy::new(initial_value, mutator, objective);
-    strategy.run(1000); // Run for 1000 generations
+    let mut strategy = CmaEs::new(initial_value, 1.0, benchmark);
+    let result = strategy.run_until(&mut MaxGenerations::new(1000)); // Run for 1000 generations
 
-    println!("Best individual: {}", strategy.best_individual());
+    println!("Best individual: {:?}", result.best_individual);
+    println!("Best fitness: {:?}", result.best_fitness);
+    println!(
+        "Known optimum: {:?} with fitness {}",
+        benchmark.optimum(dimension),
+        benchmark.optimal_value(dimension)
+    );
+    println!(
+        "Stopped after {} generations and {} evaluations in {:?} ({})",
+        result.generations, result.evaluations, result.elapsed, result.termination
+    );
 }
@@ -0,0 +1,597 @@
+// Standard continuous benchmark functions
+//
+// Every benchmark is a minimization problem in any number of dimensions with a
+// known global optimum and the search domain it is usually evaluated on. The
+// benchmarks are objectives themselves, so they can be handed to a strategy
+// directly, e.g. `CmaEs::new(start, 1.0, Rastrigin)`.
+//
+// `Transformed` moves the optimum away from the origin and rotates the search
+// space, which breaks the separability most of these functions have. It
+// evaluates f(R (x - shift)), so the optimum moves to shift + R^T x*.
+
+use rand::Rng;
+use rand_distr::StandardNormal;
+
+use crate::bounds::Bounds;
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+
+pub trait Benchmark {
+    fn value(&self, x: &[f64]) -> f64;
+
+    // Location of the global minimum
+    fn optimum(&self, dimension: usize) -> Vec<f64>;
+
+    // Value at the global minimum
+    fn optimal_value(&self, _dimension: usize) -> f64 {
+        0.0
+    }
+
+    // The usual search domain
+    fn bounds(&self, dimension: usize) -> Bounds;
+}
+
+macro_rules! impl_objective {
+    ($($benchmark:ident),*) => {
+        $(
+            impl<G: Genome<Gene = f64>> Objective<G> for $benchmark {
+                fn evaluate(&mut self, individual: &G) -> f64 {
+                    self.value(individual.genes())
+                }
+
+                fn direction(&self) -> OptimizationDirection {
+                    OptimizationDirection::Minimize
+                }
+            }
+        )*
+    };
+}
+
+impl_objective!(
+    Sphere,
+    Ellipsoid,
+    Rosenbrock,
+    Rastrigin,
+    Ackley,
+    Griewank,
+    Schwefel,
+    Levy,
+    StyblinskiTang,
+    Zakharov,
+    BentCigar,
+    Discus
+);
+
+const TAU: f64 = 2.0 * std::f64::consts::PI;
+
+// Sum of squares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere;
+
+impl Benchmark for Sphere {
+    fn value(&self, x: &[f64]) -> f64 {
+        x.iter().map(|v| v * v).sum()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -5.12, 5.12)
+    }
+}
+
+// Axis-parallel ellipsoid with a condition number of 10^6, the weights grow
+// geometrically from 1 to 10^6 along the axes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ellipsoid;
+
+impl Benchmark for Ellipsoid {
+    fn value(&self, x: &[f64]) -> f64 {
+        let n = x.len();
+        x.iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let exponent = if n > 1 {
+                    6.0 * i as f64 / (n - 1) as f64
+                } else {
+                    0.0
+                };
+                10f64.powf(exponent) * v * v
+            })
+            .sum()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -5.0, 5.0)
+    }
+}
+
+// Narrow curved valley, minimum at (1, ..., 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rosenbrock;
+
+impl Benchmark for Rosenbrock {
+    fn value(&self, x: &[f64]) -> f64 {
+        x.windows(2)
+            .map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2))
+            .sum()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![1.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -5.0, 10.0)
+    }
+}
+
+// Sphere with a regular grid of local minima
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rastrigin;
+
+impl Benchmark for Rastrigin {
+    fn value(&self, x: &[f64]) -> f64 {
+        10.0 * x.len() as f64
+            + x.iter()
+                .map(|v| v * v - 10.0 * (TAU * v).cos())
+                .sum::<f64>()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -5.12, 5.12)
+    }
+}
+
+// Nearly flat outer region with many local minima around a deep central hole
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ackley;
+
+impl Benchmark for Ackley {
+    fn value(&self, x: &[f64]) -> f64 {
+        if x.is_empty() {
+            return 0.0;
+        }
+        let n = x.len() as f64;
+        let squares = x.iter().map(|v| v * v).sum::<f64>() / n;
+        let cosines = x.iter().map(|v| (TAU * v).cos()).sum::<f64>() / n;
+        -20.0 * (-0.2 * squares.sqrt()).exp() - cosines.exp() + 20.0 + std::f64::consts::E
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -32.768, 32.768)
+    }
+}
+
+// Product of cosines over a wide parabola, the local minima fade out as the
+// dimension grows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Griewank;
+
+impl Benchmark for Griewank {
+    fn value(&self, x: &[f64]) -> f64 {
+        let sum = x.iter().map(|v| v * v).sum::<f64>() / 4000.0;
+        let product = x
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v / ((i + 1) as f64).sqrt()).cos())
+            .product::<f64>();
+        1.0 + sum - product
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -600.0, 600.0)
+    }
+}
+
+// Deceptive: the second best minimum lies far away from the global one, close
+// to the opposite corner of the domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schwefel;
+
+impl Schwefel {
+    const OPTIMUM: f64 = 420.968_746_359_982_4;
+    const OFFSET: f64 = 418.982_887_272_433_7;
+}
+
+impl Benchmark for Schwefel {
+    fn value(&self, x: &[f64]) -> f64 {
+        Self::OFFSET * x.len() as f64 - x.iter().map(|v| v * v.abs().sqrt().sin()).sum::<f64>()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![Self::OPTIMUM; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -500.0, 500.0)
+    }
+}
+
+// Minimum at (1, ..., 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Levy;
+
+impl Benchmark for Levy {
+    fn value(&self, x: &[f64]) -> f64 {
+        let w: Vec<f64> = x.iter().map(|v| 1.0 + (v - 1.0) / 4.0).collect();
+        let (Some(first), Some(last)) = (w.first(), w.last()) else {
+            return 0.0;
+        };
+        let pi = std::f64::consts::PI;
+        let middle = w[..w.len() - 1]
+            .iter()
+            .map(|w| (w - 1.0).powi(2) * (1.0 + 10.0 * (pi * w + 1.0).sin().powi(2)))
+            .sum::<f64>();
+        (pi * first).sin().powi(2)
+            + middle
+            + (last - 1.0).powi(2) * (1.0 + (TAU * last).sin().powi(2))
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![1.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -10.0, 10.0)
+    }
+}
+
+// Separable quartic with its minimum in a corner of the domain. Unlike the
+// other benchmarks its optimal value is not zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StyblinskiTang;
+
+impl StyblinskiTang {
+    // Root of 4x^3 - 32x + 5
+    const OPTIMUM: f64 = -2.903_534_027_771_178;
+}
+
+impl Benchmark for StyblinskiTang {
+    fn value(&self, x: &[f64]) -> f64 {
+        0.5 * x
+            .iter()
+            .map(|v| v.powi(4) - 16.0 * v * v + 5.0 * v)
+            .sum::<f64>()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![Self::OPTIMUM; dimension]
+    }
+
+    fn optimal_value(&self, dimension: usize) -> f64 {
+        self.value(&self.optimum(dimension))
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -5.0, 5.0)
+    }
+}
+
+// Plate-shaped, with a weighted sum that couples all variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Zakharov;
+
+impl Benchmark for Zakharov {
+    fn value(&self, x: &[f64]) -> f64 {
+        let squares = x.iter().map(|v| v * v).sum::<f64>();
+        let weighted = x
+            .iter()
+            .enumerate()
+            .map(|(i, v)| 0.5 * (i + 1) as f64 * v)
+            .sum::<f64>();
+        squares + weighted.powi(2) + weighted.powi(4)
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -5.0, 10.0)
+    }
+}
+
+// One sensitive direction: every variable but the first is weighted by 10^6
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BentCigar;
+
+impl Benchmark for BentCigar {
+    fn value(&self, x: &[f64]) -> f64 {
+        let Some((first, rest)) = x.split_first() else {
+            return 0.0;
+        };
+        first * first + 1e6 * rest.iter().map(|v| v * v).sum::<f64>()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -100.0, 100.0)
+    }
+}
+
+// The opposite of the bent cigar: only the first variable is weighted by 10^6
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Discus;
+
+impl Benchmark for Discus {
+    fn value(&self, x: &[f64]) -> f64 {
+        let Some((first, rest)) = x.split_first() else {
+            return 0.0;
+        };
+        1e6 * first * first + rest.iter().map(|v| v * v).sum::<f64>()
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        vec![0.0; dimension]
+    }
+
+    fn bounds(&self, dimension: usize) -> Bounds {
+        Bounds::uniform(dimension, -100.0, 100.0)
+    }
+}
+
+// A benchmark with its optimum shifted and its search space rotated. Both are
+// optional and fix the dimension of the benchmark.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transformed<B> {
+    benchmark: B,
+    shift: Option<Vec<f64>>,
+    rotation: Option<Vec<Vec<f64>>>,
+}
+
+impl<B: Benchmark> Transformed<B> {
+    pub fn new(benchmark: B) -> Self {
+        Transformed {
+            benchmark,
+            shift: None,
+            rotation: None,
+        }
+    }
+
+    pub fn with_shift(mut self, shift: Vec<f64>) -> Self {
+        if let Some(rotation) = &self.rotation {
+            assert_eq!(shift.len(), rotation.len(), "Dimension mismatch");
+        }
+        self.shift = Some(shift);
+        self
+    }
+
+    // The rotation must be an orthogonal matrix, see `random_rotation`
+    pub fn with_rotation(mut self, rotation: Vec<Vec<f64>>) -> Self {
+        let n = rotation.len();
+        assert!(
+            rotation.iter().all(|row| row.len() == n),
+            "The rotation must be a square matrix"
+        );
+        for (i, a) in rotation.iter().enumerate() {
+            for (j, b) in rotation.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!(
+                    (dot(a, b) - expected).abs() < 1e-9,
+                    "The rotation must be orthogonal"
+                );
+            }
+        }
+        if let Some(shift) = &self.shift {
+            assert_eq!(shift.len(), n, "Dimension mismatch");
+        }
+        self.rotation = Some(rotation);
+        self
+    }
+
+    // The dimension fixed by the shift or rotation, if any
+    pub fn dimension(&self) -> Option<usize> {
+        self.shift
+            .as_ref()
+            .map(Vec::len)
+            .or(self.rotation.as_ref().map(Vec::len))
+    }
+}
+
+impl<B: Benchmark> Benchmark for Transformed<B> {
+    fn value(&self, x: &[f64]) -> f64 {
+        if let Some(dimension) = self.dimension() {
+            assert_eq!(x.len(), dimension, "Dimension mismatch");
+        }
+        let mut z = x.to_vec();
+        if let Some(shift) = &self.shift {
+            for (z, s) in z.iter_mut().zip(shift) {
+                *z -= s;
+            }
+        }
+        if let Some(rotation) = &self.rotation {
+            z = rotation.iter().map(|row| dot(row, &z)).collect();
+        }
+        self.benchmark.value(&z)
+    }
+
+    fn optimum(&self, dimension: usize) -> Vec<f64> {
+        let mut x = self.benchmark.optimum(dimension);
+        if let Some(rotation) = &self.rotation {
+            x = (0..dimension)
+                .map(|j| rotation.iter().zip(&x).map(|(row, v)| row[j] * v).sum())
+                .collect();
+        }
+        if let Some(shift) = &self.shift {
+            for (x, s) in x.iter_mut().zip(shift) {
+                *x += s;
+            }
+        }
+        x
+    }
+
+    fn optimal_value(&self, dimension: usize) -> f64 {
+        self.benchmark.optimal_value(dimension)
+    }
+
+    // The domain of the untransformed benchmark. A large shift or a rotation
+    // can move the optimum out of it.
+    fn bounds(&self, dimension: usize) -> Bounds {
+        self.benchmark.bounds(dimension)
+    }
+}
+
+impl<G: Genome<Gene = f64>, B: Benchmark> Objective<G> for Transformed<B> {
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        self.value(individual.genes())
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        OptimizationDirection::Minimize
+    }
+}
+
+// Uniformly distributed orthogonal matrix, from Gram-Schmidt orthonormalization
+// of Gaussian vectors
+pub fn random_rotation<R: Rng + ?Sized>(dimension: usize, rng: &mut R) -> Vec<Vec<f64>> {
+    let mut rows: Vec<Vec<f64>> = Vec::with_capacity(dimension);
+    while rows.len() < dimension {
+        let mut v: Vec<f64> = (0..dimension).map(|_| rng.sample(StandardNormal)).collect();
+        for row in &rows {
+            let projection = dot(&v, row);
+            for (v, r) in v.iter_mut().zip(row) {
+                *v -= projection * r;
+            }
+        }
+        let norm = dot(&v, &v).sqrt();
+        if norm > 1e-8 {
+            rows.push(v.iter().map(|v| v / norm).collect());
+        }
+    }
+    rows
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution_strategies::CmaEs;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn all() -> Vec<Box<dyn Benchmark>> {
+        vec![
+            Box::new(Sphere),
+            Box::new(Ellipsoid),
+            Box::new(Rosenbrock),
+            Box::new(Rastrigin),
+            Box::new(Ackley),
+            Box::new(Griewank),
+            Box::new(Schwefel),
+            Box::new(Levy),
+            Box::new(StyblinskiTang),
+            Box::new(Zakharov),
+            Box::new(BentCigar),
+            Box::new(Discus),
+        ]
+    }
+
+    #[test]
+    fn test_known_optima() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for (i, benchmark) in all().iter().enumerate() {
+            for dimension in [2, 5, 10] {
+                let optimum = benchmark.optimum(dimension);
+                let optimal_value = benchmark.optimal_value(dimension);
+                assert!(benchmark.bounds(dimension).contains(&optimum));
+                assert!(
+                    (benchmark.value(&optimum) - optimal_value).abs() < 1e-9,
+                    "benchmark {} in {} dimensions",
+                    i,
+                    dimension
+                );
+
+                // No random point of the domain is better than the optimum
+                let bounds = benchmark.bounds(dimension);
+                for _ in 0..100 {
+                    assert!(benchmark.value(&bounds.sample(&mut rng)) > optimal_value);
+                }
+            }
+        }
+        assert!((StyblinskiTang.optimal_value(2) + 78.332_331_407_5).abs() < 1e-6);
+
+        // Every benchmark is defined for zero dimensions
+        for benchmark in all() {
+            assert_eq!(benchmark.value(&[]), benchmark.optimal_value(0));
+        }
+    }
+
+    #[test]
+    fn test_transformed() {
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        let shift = vec![1.0, -2.0, 0.5, 3.0];
+        let rotation = random_rotation(4, &mut rng);
+        for (i, a) in rotation.iter().enumerate() {
+            assert!((dot(a, a) - 1.0).abs() < 1e-12);
+            for b in &rotation[i + 1..] {
+                assert!(dot(a, b).abs() < 1e-12);
+            }
+        }
+
+        let benchmark = Transformed::new(Rosenbrock)
+            .with_shift(shift.clone())
+            .with_rotation(rotation);
+        let optimum = benchmark.optimum(4);
+        assert!(benchmark.value(&optimum).abs() < 1e-20);
+        assert!(benchmark.value(&shift) > 1.0);
+
+        // Without a rotation the optimum is just shifted
+        let benchmark = Transformed::new(Sphere).with_shift(shift.clone());
+        assert_eq!(benchmark.optimum(4), shift);
+        assert_eq!(benchmark.value(&[0.0; 4]), Sphere.value(&shift));
+    }
+
+    #[test]
+    fn test_cma_es_on_rotated_ellipsoid() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let benchmark = Transformed::new(Ellipsoid)
+            .with_shift(vec![2.0, -1.0, 1.0, 0.5, -3.0])
+            .with_rotation(random_rotation(5, &mut rng));
+        let optimum = benchmark.optimum(5);
+
+        let mut strategy = CmaEs::new(vec![0.0; 5], 1.0, benchmark).with_seed(3);
+        strategy.run(600);
+        assert!(strategy.best_fitness().unwrap() < 1e-10);
+        for (x, o) in strategy.best_individual().iter().zip(&optimum) {
+            assert!((x - o).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,425 @@
+// Module for box constraints on real-valued genomes
+//
+// `Bounds` holds a lower and upper limit per dimension together with the way
+// genes outside of the box are handled. Operators and objectives are bounded
+// by wrapping them in `Bounded`: wrapped mutators, recombinators and crossovers
+// repair every individual they produce, and a wrapped objective never sees an
+// infeasible genome. With `BoundaryHandling::Penalty` the genome itself is left
+// unchanged and the objective is evaluated at the nearest feasible point plus
+// a penalty proportional to the squared distance to the box.
+
+use rand::Rng;
+
+use crate::evolution_strategies::{AdaptiveMutate, Mutate, Recombine};
+use crate::genetic_algorithms::Crossover;
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundaryHandling {
+    // Set the gene to the violated bound
+    Clamp,
+    // Mirror the gene back into the box at the violated bound
+    Reflect,
+    // Treat the box as periodic
+    Wrap,
+    // Draw the gene uniformly from its interval
+    Resample,
+    // Keep the gene and penalize the fitness
+    Penalty { weight: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounds {
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    handling: BoundaryHandling,
+}
+
+impl Bounds {
+    pub fn new(lower: Vec<f64>, upper: Vec<f64>) -> Bounds {
+        assert_eq!(
+            lower.len(),
+            upper.len(),
+            "Lower and upper bounds must have the same dimension"
+        );
+        assert!(
+            lower.iter().zip(&upper).all(|(l, u)| l <= u),
+            "Every lower bound must not exceed its upper bound"
+        );
+        Bounds {
+            lower,
+            upper,
+            handling: BoundaryHandling::Clamp,
+        }
+    }
+
+    // The same interval in every dimension
+    pub fn uniform(dimension: usize, lower: f64, upper: f64) -> Bounds {
+        Bounds::new(vec![lower; dimension], vec![upper; dimension])
+    }
+
+    pub fn with_handling(mut self, handling: BoundaryHandling) -> Self {
+        self.handling = handling;
+        self
+    }
+
+    pub fn lower(&self) -> &[f64] {
+        &self.lower
+    }
+
+    pub fn upper(&self) -> &[f64] {
+        &self.upper
+    }
+
+    pub fn handling(&self) -> BoundaryHandling {
+        self.handling
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.lower.len()
+    }
+
+    pub fn contains(&self, genes: &[f64]) -> bool {
+        genes
+            .iter()
+            .zip(self.lower.iter().zip(&self.upper))
+            .all(|(x, (l, u))| l <= x && x <= u)
+    }
+
+    // Sum of squared distances of the genes to the box
+    pub fn violation(&self, genes: &[f64]) -> f64 {
+        genes
+            .iter()
+            .zip(self.lower.iter().zip(&self.upper))
+            .map(|(x, (l, u))| {
+                if x < l {
+                    (l - x).powi(2)
+                } else if x > u {
+                    (x - u).powi(2)
+                } else {
+                    0.0
+                }
+            })
+            .sum()
+    }
+
+    // A point drawn uniformly from the box
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<f64> {
+        self.lower
+            .iter()
+            .zip(&self.upper)
+            .map(|(l, u)| if l < u { rng.gen_range(*l..*u) } else { *l })
+            .collect()
+    }
+
+    // Moves every gene back into the box. Penalty handling leaves the genome unchanged.
+    pub fn repair<G: Genome<Gene = f64>, R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        assert_eq!(
+            individual.dimension(),
+            self.dimension(),
+            "The genome does not match the dimension of the bounds"
+        );
+        if let BoundaryHandling::Penalty { .. } = self.handling {
+            return;
+        }
+
+        for (x, (l, u)) in individual
+            .genes_mut()
+            .iter_mut()
+            .zip(self.lower.iter().zip(&self.upper))
+        {
+            if *l <= *x && *x <= *u {
+                continue;
+            }
+            let width = u - l;
+            if width == 0.0 || !x.is_finite() {
+                *x = *l;
+                continue;
+            }
+            *x = match self.handling {
+                BoundaryHandling::Clamp => x.clamp(*l, *u),
+                BoundaryHandling::Reflect => {
+                    let t = (*x - l).rem_euclid(2.0 * width);
+                    if t > width {
+                        l + 2.0 * width - t
+                    } else {
+                        l + t
+                    }
+                }
+                BoundaryHandling::Wrap => l + (*x - l).rem_euclid(width),
+                BoundaryHandling::Resample => rng.gen_range(*l..*u),
+                BoundaryHandling::Penalty { .. } => unreachable!(),
+            };
+        }
+    }
+
+    // The point at which the objective is evaluated for `individual`: the
+    // individual itself if it is feasible, its nearest feasible point otherwise
+    pub fn evaluation_point<G: Genome<Gene = f64>>(&self, individual: &G) -> G {
+        let mut feasible = individual.clone();
+        for (x, (l, u)) in feasible
+            .genes_mut()
+            .iter_mut()
+            .zip(self.lower.iter().zip(&self.upper))
+        {
+            *x = x.clamp(*l, *u);
+        }
+        feasible
+    }
+
+    // Adds the penalty of `individual` to the fitness of its evaluation point
+    pub fn penalize<G: Genome<Gene = f64>>(
+        &self,
+        fitness: f64,
+        individual: &G,
+        direction: OptimizationDirection,
+    ) -> f64 {
+        match self.handling {
+            BoundaryHandling::Penalty { weight } => {
+                let penalty = weight * self.violation(individual.genes());
+                match direction {
+                    OptimizationDirection::Minimize => fitness + penalty,
+                    OptimizationDirection::Maximize => fitness - penalty,
+                }
+            }
+            _ => fitness,
+        }
+    }
+
+    // Evaluates the objective without ever passing it an infeasible genome
+    pub fn evaluate<G: Genome<Gene = f64>, O: Objective<G>>(
+        &self,
+        objective: &mut O,
+        individual: &G,
+    ) -> f64 {
+        if self.contains(individual.genes()) {
+            return objective.evaluate(individual);
+        }
+
+        let fitness = objective.evaluate(&self.evaluation_point(individual));
+        self.penalize(fitness, individual, objective.direction())
+    }
+}
+
+// Restricts a mutator, recombinator, crossover or objective to a box
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounded<T> {
+    inner: T,
+    bounds: Bounds,
+}
+
+impl<T> Bounded<T> {
+    pub fn new(inner: T, bounds: Bounds) -> Bounded<T> {
+        Bounded { inner, bounds }
+    }
+
+    pub fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+}
+
+impl<G: Genome<Gene = f64>, M: Mutate<G>> Mutate<G> for Bounded<M> {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        self.inner.mutate(individual, rng);
+        self.bounds.repair(individual, rng);
+    }
+}
+
+// Repairs after every mutation, so the step size control of the inner
+// mutator is unaffected
+impl<G: Genome<Gene = f64>, M: AdaptiveMutate<G>> AdaptiveMutate<G> for Bounded<M> {
+    fn step_size(&self) -> f64 {
+        self.inner.step_size()
+    }
+
+    fn set_step_size(&mut self, step_size: f64) {
+        self.inner.set_step_size(step_size);
+    }
+}
+
+impl<G: Genome<Gene = f64>, C: Recombine<G>> Recombine<G> for Bounded<C> {
+    fn recombine<R: Rng + ?Sized>(&self, parents: &[G], rng: &mut R) -> G {
+        let mut offspring = self.inner.recombine(parents, rng);
+        self.bounds.repair(&mut offspring, rng);
+        offspring
+    }
+}
+
+impl<G: Genome<Gene = f64>, C: Crossover<G>> Crossover<G> for Bounded<C> {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G) {
+        let (mut a, mut b) = self.inner.crossover(first, second, rng);
+        self.bounds.repair(&mut a, rng);
+        self.bounds.repair(&mut b, rng);
+        (a, b)
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>> Objective<G> for Bounded<O> {
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        self.bounds.evaluate(&mut self.inner, individual)
+    }
+
+    fn evaluate_batch(&mut self, individuals: &[G]) -> Vec<f64> {
+        let points: Vec<G> = individuals
+            .iter()
+            .map(|individual| self.bounds.evaluation_point(individual))
+            .collect();
+        let direction = self.inner.direction();
+        self.inner
+            .evaluate_batch(&points)
+            .into_iter()
+            .zip(individuals)
+            .map(|(fitness, individual)| self.bounds.penalize(fitness, individual, direction))
+            .collect()
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.inner.direction()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution_strategies::{
+        AdaptiveOnePlusOneStrategy, GaussianMutator, IntermediateRecombination,
+        MuCommaLambdaStrategy, MuPlusLambdaStrategy, OnePlusOneStrategy, SimpleSelector,
+    };
+    use crate::genetic_algorithms::{GeneticAlgorithm, SbxCrossover};
+    use crate::objective::Minimize;
+    use crate::testing::random_population;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn repaired(handling: BoundaryHandling, genes: Vec<f64>) -> Vec<f64> {
+        let bounds = Bounds::uniform(genes.len(), 0.0, 1.0).with_handling(handling);
+        let mut genes = genes;
+        bounds.repair(&mut genes, &mut ChaCha8Rng::seed_from_u64(1));
+        genes
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-12, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_boundary_handling_modes() {
+        let genes = vec![-0.25, 0.5, 1.25, 2.75];
+
+        assert_close(
+            &repaired(BoundaryHandling::Clamp, genes.clone()),
+            &[0.0, 0.5, 1.0, 1.0],
+        );
+        assert_close(
+            &repaired(BoundaryHandling::Reflect, genes.clone()),
+            &[0.25, 0.5, 0.75, 0.75],
+        );
+        assert_close(
+            &repaired(BoundaryHandling::Wrap, genes.clone()),
+            &[0.75, 0.5, 0.25, 0.75],
+        );
+        assert_close(
+            &repaired(BoundaryHandling::Penalty { weight: 1.0 }, genes.clone()),
+            &genes,
+        );
+
+        let resampled = repaired(BoundaryHandling::Resample, genes);
+        assert!(Bounds::uniform(4, 0.0, 1.0).contains(&resampled));
+        assert_eq!(resampled[1], 0.5);
+    }
+
+    #[test]
+    fn test_penalty_never_evaluates_outside_bounds() {
+        let bounds =
+            Bounds::uniform(2, -1.0, 1.0).with_handling(BoundaryHandling::Penalty { weight: 10.0 });
+        let mut objective = Bounded::new(
+            Minimize(|x: &Vec<f64>| -> f64 {
+                assert!(x.iter().all(|v| v.abs() <= 1.0));
+                x.iter().map(|v| v * v).sum()
+            }),
+            bounds,
+        );
+
+        assert_eq!(objective.evaluate(&vec![0.5, 0.0]), 0.25);
+        // Clamped to (1, 0) with fitness 1 plus a penalty of 10 * 1^2
+        assert_eq!(objective.evaluate(&vec![2.0, 0.0]), 11.0);
+    }
+
+    #[test]
+    fn test_bounded_mutation_stays_in_box() {
+        // The unconstrained optimum at (3, 3) lies outside the box
+        let bounds =
+            Bounds::new(vec![-1.0, -2.0], vec![1.0, 2.0]).with_handling(BoundaryHandling::Reflect);
+        let objective = |x: &Vec<f64>| -> f64 {
+            assert!(x[0].abs() <= 1.0 && x[1].abs() <= 2.0);
+            -(x[0] - 3.0).powi(2) - (x[1] - 3.0).powi(2)
+        };
+        let mutator = Bounded::new(GaussianMutator::new(1.0, 2.0), bounds);
+
+        let mut strategy = OnePlusOneStrategy::new(vec![0.0, 0.0], mutator, objective).with_seed(1);
+        strategy.run(2000);
+
+        let best = strategy.best_individual();
+        assert!((best[0] - 1.0).abs() < 0.05 && (best[1] - 2.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_bounded_operators_keep_strategies_in_box() {
+        // The unconstrained optimum at (3, 3) lies outside the box, which the
+        // objective checks for every candidate
+        let bounds = Bounds::uniform(2, -1.0, 1.0).with_handling(BoundaryHandling::Reflect);
+        let in_box = Minimize(|x: &Vec<f64>| -> f64 {
+            assert!(x.iter().all(|v| v.abs() <= 1.0), "{:?}", x);
+            x.iter().map(|v| (v - 3.0).powi(2)).sum()
+        });
+        let mutator = || Bounded::new(GaussianMutator::new(1.0, 2.0), bounds.clone());
+        let population: Vec<Vec<f64>> = random_population(10, 2, 1)
+            .into_iter()
+            .map(|x| x.iter().map(|v| v / 5.0).collect())
+            .collect();
+
+        let mut adaptive = AdaptiveOnePlusOneStrategy::new(vec![0.0, 0.0], mutator(), in_box)
+            .with_window(5)
+            .with_seed(1);
+        adaptive.run(300);
+        assert!(bounds.contains(adaptive.best_individual()));
+
+        let recombination = Bounded::new(IntermediateRecombination::new(2), bounds.clone());
+        let mut comma = MuCommaLambdaStrategy::new(
+            population.clone(),
+            20,
+            mutator(),
+            SimpleSelector::new(5, in_box),
+        )
+        .with_recombination(recombination)
+        .with_seed(1);
+        comma.run(100);
+        assert!(comma.population().iter().all(|x| bounds.contains(x)));
+
+        let mut plus = MuPlusLambdaStrategy::new(
+            population.clone(),
+            20,
+            mutator(),
+            SimpleSelector::new(5, in_box),
+        )
+        .with_seed(1);
+        plus.run(100);
+        assert!(plus.population().iter().all(|x| bounds.contains(x)));
+
+        let mut ga = GeneticAlgorithm::new(
+            population,
+            Bounded::new(SbxCrossover::default(), bounds.clone()),
+            mutator(),
+            SimpleSelector::new(5, in_box),
+        )
+        .with_seed(1);
+        ga.run(100);
+        assert!(ga.population().iter().all(|x| bounds.contains(x)));
+        assert!(bounds.contains(ga.best_individual()));
+    }
+}
@@ -0,0 +1,610 @@
+// Module for general nonlinear constraints
+//
+// Constraints report one violation per constraint, zero when it is satisfied.
+// How infeasible candidates compare against feasible ones is decided by a
+// `ConstraintHandling` policy, which orders a whole set of evaluated
+// candidates at once. This lets population level schemes like stochastic
+// ranking or adaptive penalties work the same way for selectors and for
+// strategies.
+
+use rand::Rng;
+
+use crate::objective::OptimizationDirection;
+
+pub trait Constraints<G> {
+    fn violations(&mut self, individual: &G) -> Vec<f64>;
+}
+
+impl<G, F> Constraints<G> for F
+where
+    F: FnMut(&G) -> Vec<f64>,
+{
+    fn violations(&mut self, individual: &G) -> Vec<f64> {
+        self(individual)
+    }
+}
+
+// Violation of an inequality constraint g(x) <= 0
+pub fn inequality(g: f64) -> f64 {
+    g.max(0.0)
+}
+
+// Violation of an equality constraint h(x) = 0, satisfied within `tolerance`
+pub fn equality(h: f64, tolerance: f64) -> f64 {
+    (h.abs() - tolerance).max(0.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstrainedFitness {
+    pub fitness: f64,
+    // Sum of all constraint violations
+    pub violation: f64,
+}
+
+impl ConstrainedFitness {
+    pub fn is_feasible(&self) -> bool {
+        self.violation <= 0.0
+    }
+}
+
+pub trait ConstraintHandling {
+    // Returns the candidate indices ordered best first. Each call is treated
+    // as one generation by adaptive policies.
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        candidates: &[ConstrainedFitness],
+        direction: OptimizationDirection,
+        rng: &mut R,
+    ) -> Vec<usize>;
+}
+
+fn sorted_indices<F>(candidates: &[ConstrainedFitness], mut compare: F) -> Vec<usize>
+where
+    F: FnMut(&ConstrainedFitness, &ConstrainedFitness) -> std::cmp::Ordering,
+{
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|a, b| compare(&candidates[*a], &candidates[*b]));
+    order
+}
+
+fn penalized(candidate: &ConstrainedFitness, weight: f64, direction: OptimizationDirection) -> f64 {
+    match direction {
+        OptimizationDirection::Minimize => candidate.fitness + weight * candidate.violation,
+        OptimizationDirection::Maximize => candidate.fitness - weight * candidate.violation,
+    }
+}
+
+// Adds a fixed multiple of the violation to the fitness
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StaticPenalty {
+    weight: f64,
+}
+
+impl StaticPenalty {
+    pub fn new(weight: f64) -> StaticPenalty {
+        StaticPenalty { weight }
+    }
+}
+
+impl ConstraintHandling for StaticPenalty {
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        candidates: &[ConstrainedFitness],
+        direction: OptimizationDirection,
+        _rng: &mut R,
+    ) -> Vec<usize> {
+        sorted_indices(candidates, |a, b| {
+            direction.compare(
+                penalized(a, self.weight, direction),
+                penalized(b, self.weight, direction),
+            )
+        })
+    }
+}
+
+// Penalty whose weight adapts to the feasibility of the best candidate over
+// the last `window` generations (Bean and Hadj-Alouane). If the best was always
+// feasible the weight shrinks by `decrease`, if it was always infeasible the
+// weight grows by `increase`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptivePenalty {
+    weight: f64,
+    window: usize,
+    decrease: f64,
+    increase: f64,
+    history: Vec<bool>,
+}
+
+impl AdaptivePenalty {
+    pub fn new(initial_weight: f64) -> AdaptivePenalty {
+        AdaptivePenalty {
+            weight: initial_weight,
+            window: 5,
+            decrease: 1.5,
+            increase: 2.0,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn with_window(mut self, window: usize) -> Self {
+        assert!(window > 0, "The adaptation window must not be empty");
+        self.window = window;
+        self
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+impl ConstraintHandling for AdaptivePenalty {
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        candidates: &[ConstrainedFitness],
+        direction: OptimizationDirection,
+        _rng: &mut R,
+    ) -> Vec<usize> {
+        let weight = self.weight;
+        let order = sorted_indices(candidates, |a, b| {
+            direction.compare(
+                penalized(a, weight, direction),
+                penalized(b, weight, direction),
+            )
+        });
+
+        if let Some(best) = order.first() {
+            self.history.push(candidates[*best].is_feasible());
+            if self.history.len() == self.window {
+                if self.history.iter().all(|feasible| *feasible) {
+                    self.weight /= self.decrease;
+                } else if self.history.iter().all(|feasible| !*feasible) {
+                    self.weight *= self.increase;
+                }
+                self.history.clear();
+            }
+        }
+
+        order
+    }
+}
+
+// Deb's feasibility rules: feasible candidates beat infeasible ones, feasible
+// candidates are compared by fitness and infeasible ones by violation
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeasibilityRules;
+
+impl ConstraintHandling for FeasibilityRules {
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        candidates: &[ConstrainedFitness],
+        direction: OptimizationDirection,
+        _rng: &mut R,
+    ) -> Vec<usize> {
+        sorted_indices(candidates, |a, b| {
+            if a.is_feasible() && b.is_feasible() {
+                direction.compare(a.fitness, b.fitness)
+            } else {
+                a.violation.total_cmp(&b.violation)
+            }
+        })
+    }
+}
+
+// Stochastic ranking (Runarsson and Yao): a bubble sort where adjacent
+// candidates are compared by fitness if both are feasible or with probability
+// `pf`, and by violation otherwise
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StochasticRanking {
+    pf: f64,
+}
+
+impl StochasticRanking {
+    pub fn new(pf: f64) -> StochasticRanking {
+        assert!((0.0..=1.0).contains(&pf), "pf must be a probability");
+        StochasticRanking { pf }
+    }
+}
+
+impl Default for StochasticRanking {
+    fn default() -> Self {
+        StochasticRanking::new(0.45)
+    }
+}
+
+impl ConstraintHandling for StochasticRanking {
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        candidates: &[ConstrainedFitness],
+        direction: OptimizationDirection,
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        for _ in 0..candidates.len() {
+            let mut swapped = false;
+            for j in 0..order.len().saturating_sub(1) {
+                let (a, b) = (&candidates[order[j]], &candidates[order[j + 1]]);
+                let by_fitness = (a.is_feasible() && b.is_feasible()) || rng.gen::<f64>() < self.pf;
+                let swap = if by_fitness {
+                    direction.is_better(b.fitness, a.fitness)
+                } else {
+                    b.violation < a.violation
+                };
+                if swap {
+                    order.swap(j, j + 1);
+                    swapped = true;
+                }
+            }
+            if !swapped {
+                break;
+            }
+        }
+        order
+    }
+}
+
+// Epsilon-constraint method (Takahama and Sakai): violations below epsilon
+// count as feasible. Epsilon starts at the violation of the top `theta`
+// fraction of the first generation and decays to zero over
+// `control_generations` generations.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EpsilonConstraint {
+    control_generations: usize,
+    cp: f64,
+    theta: f64,
+    initial_epsilon: Option<f64>,
+    generation: usize,
+}
+
+impl EpsilonConstraint {
+    pub fn new(control_generations: usize) -> EpsilonConstraint {
+        EpsilonConstraint {
+            control_generations,
+            cp: 5.0,
+            theta: 0.2,
+            initial_epsilon: None,
+            generation: 0,
+        }
+    }
+
+    pub fn with_cp(mut self, cp: f64) -> Self {
+        self.cp = cp;
+        self
+    }
+
+    pub fn with_theta(mut self, theta: f64) -> Self {
+        assert!((0.0..=1.0).contains(&theta), "theta must be a fraction");
+        self.theta = theta;
+        self
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        let initial = self.initial_epsilon.unwrap_or(0.0);
+        if self.generation >= self.control_generations {
+            0.0
+        } else {
+            let progress = self.generation as f64 / self.control_generations as f64;
+            initial * (1.0 - progress).powf(self.cp)
+        }
+    }
+}
+
+impl ConstraintHandling for EpsilonConstraint {
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        candidates: &[ConstrainedFitness],
+        direction: OptimizationDirection,
+        _rng: &mut R,
+    ) -> Vec<usize> {
+        if self.initial_epsilon.is_none() && !candidates.is_empty() {
+            let mut violations: Vec<f64> = candidates.iter().map(|c| c.violation).collect();
+            violations.sort_by(|a, b| a.total_cmp(b));
+            let index = ((self.theta * violations.len() as f64) as usize).min(violations.len() - 1);
+            self.initial_epsilon = Some(violations[index]);
+        }
+
+        let epsilon = self.epsilon();
+        let order = sorted_indices(candidates, |a, b| {
+            let both_within = a.violation <= epsilon && b.violation <= epsilon;
+            if both_within || a.violation == b.violation {
+                direction.compare(a.fitness, b.fitness)
+            } else {
+                a.violation.total_cmp(&b.violation)
+            }
+        });
+        self.generation += 1;
+        order
+    }
+}
+
+// Ranks genomes by fitness, optionally taking constraints into account. This
+// is what selectors and strategies use to order candidates. Violations are
+// computed once per genome, next to its fitness, and ranking only looks at the
+// stored values.
+pub trait Ranking<G> {
+    // The summed constraint violation of every genome
+    fn violations(&mut self, population: &[G]) -> Vec<f64> {
+        vec![0.0; population.len()]
+    }
+
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        fitness: &[f64],
+        violations: &[f64],
+        direction: OptimizationDirection,
+        rng: &mut R,
+    ) -> Vec<usize>;
+}
+
+// Ignores the constraints entirely
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unconstrained;
+
+impl<G> Ranking<G> for Unconstrained {
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        fitness: &[f64],
+        _violations: &[f64],
+        direction: OptimizationDirection,
+        _rng: &mut R,
+    ) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..fitness.len()).collect();
+        order.sort_by(|a, b| direction.compare(fitness[*a], fitness[*b]));
+        order
+    }
+}
+
+// A set of constraints together with the policy used to handle them
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Constrained<C, H> {
+    constraints: C,
+    handling: H,
+}
+
+impl<C, H> Constrained<C, H> {
+    pub fn new(constraints: C, handling: H) -> Constrained<C, H> {
+        Constrained {
+            constraints,
+            handling,
+        }
+    }
+
+    pub fn handling(&self) -> &H {
+        &self.handling
+    }
+}
+
+impl<G, C: Constraints<G>, H: ConstraintHandling> Ranking<G> for Constrained<C, H> {
+    fn violations(&mut self, population: &[G]) -> Vec<f64> {
+        population
+            .iter()
+            .map(|individual| self.constraints.violations(individual).iter().sum())
+            .collect()
+    }
+
+    fn rank<R: Rng + ?Sized>(
+        &mut self,
+        fitness: &[f64],
+        violations: &[f64],
+        direction: OptimizationDirection,
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let candidates: Vec<ConstrainedFitness> = fitness
+            .iter()
+            .zip(violations)
+            .map(|(fitness, violation)| ConstrainedFitness {
+                fitness: *fitness,
+                violation: *violation,
+            })
+            .collect();
+        self.handling.rank(&candidates, direction, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::Sphere;
+    use crate::differential_evolution::DifferentialEvolution;
+    use crate::evolution_strategies::{
+        AdaptiveOnePlusOneStrategy, CmaEs, GaussianMutator, OnePlusOneStrategy, Select,
+        SimpleSelector,
+    };
+    use crate::objective::Minimize;
+    use crate::particle_swarm::ParticleSwarm;
+    use crate::testing::random_population;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::cell::Cell;
+
+    fn candidates() -> Vec<ConstrainedFitness> {
+        vec![
+            ConstrainedFitness {
+                fitness: 1.0,
+                violation: 0.5,
+            },
+            ConstrainedFitness {
+                fitness: 5.0,
+                violation: 0.0,
+            },
+            ConstrainedFitness {
+                fitness: 3.0,
+                violation: 0.0,
+            },
+            ConstrainedFitness {
+                fitness: 0.0,
+                violation: 2.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_policies_rank_candidates() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let minimize = OptimizationDirection::Minimize;
+
+        assert_eq!(
+            FeasibilityRules.rank(&candidates(), minimize, &mut rng),
+            vec![2, 1, 0, 3]
+        );
+        // Penalized fitness: 1 + 10 * 0.5 = 6, 5, 3, 0 + 10 * 2 = 20
+        assert_eq!(
+            StaticPenalty::new(10.0).rank(&candidates(), minimize, &mut rng),
+            vec![2, 1, 0, 3]
+        );
+        // With pf = 0 stochastic ranking behaves like the feasibility rules
+        assert_eq!(
+            StochasticRanking::new(0.0).rank(&candidates(), minimize, &mut rng),
+            vec![2, 1, 0, 3]
+        );
+        // Epsilon starts at the smallest violation, so only the feasible ones count as feasible
+        assert_eq!(
+            EpsilonConstraint::new(10).rank(&candidates(), minimize, &mut rng),
+            vec![2, 1, 0, 3]
+        );
+    }
+
+    #[test]
+    fn test_adaptive_penalty_grows_while_best_is_infeasible() {
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        let mut policy = AdaptivePenalty::new(0.1).with_window(2);
+        for _ in 0..4 {
+            policy.rank(&candidates(), OptimizationDirection::Minimize, &mut rng);
+        }
+
+        assert!(policy.weight() > 0.1);
+    }
+
+    #[test]
+    fn test_epsilon_decays_to_zero() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let mut policy = EpsilonConstraint::new(5).with_theta(1.0);
+        policy.rank(&candidates(), OptimizationDirection::Minimize, &mut rng);
+        assert!(policy.epsilon() > 0.0 && policy.epsilon() < 2.0);
+
+        for _ in 0..5 {
+            policy.rank(&candidates(), OptimizationDirection::Minimize, &mut rng);
+        }
+        assert_eq!(policy.epsilon(), 0.0);
+    }
+
+    #[test]
+    fn test_constrained_selector() {
+        // Minimize x^2 subject to x >= 1
+        let constraints = |x: &f64| vec![inequality(1.0 - x)];
+        let mut selector = SimpleSelector::new(2, Minimize(|x: &f64| x * x))
+            .with_constraints(Constrained::new(constraints, FeasibilityRules));
+
+        let selected = selector.select(&[0.0, 0.5, 3.0, 1.5], &mut ChaCha8Rng::seed_from_u64(4));
+        assert_eq!(selected, vec![1.5, 3.0]);
+    }
+
+    #[test]
+    fn test_constrained_one_plus_one() {
+        // Minimize x^2 + y^2 subject to x + y >= 2, optimum at (1, 1)
+        let objective = Minimize(|x: &Vec<f64>| -> f64 { x.iter().map(|v| v * v).sum() });
+        let constraints = |x: &Vec<f64>| vec![inequality(2.0 - x[0] - x[1])];
+
+        let mut strategy =
+            OnePlusOneStrategy::new(vec![0.0, 0.0], GaussianMutator::new(1.0, 0.05), objective)
+                .with_constraints(Constrained::new(constraints, FeasibilityRules))
+                .with_seed(11);
+        strategy.run(5000);
+
+        let best = strategy.best_individual();
+        assert!(best[0] + best[1] >= 2.0);
+        assert!((best[0] - 1.0).abs() < 0.1 && (best[1] - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_constrained_adaptive_one_plus_one() {
+        // Minimize x^2 + y^2 subject to x + y >= 2, optimum at (1, 1)
+        let constraints = |x: &Vec<f64>| vec![inequality(2.0 - x[0] - x[1])];
+
+        let mut strategy =
+            AdaptiveOnePlusOneStrategy::new(vec![0.0, 0.0], GaussianMutator::new(1.0, 1.0), Sphere)
+                .with_constraints(Constrained::new(constraints, FeasibilityRules))
+                .with_seed(12);
+        strategy.run(3000);
+
+        let best = strategy.best_individual();
+        assert!(best[0] + best[1] >= 2.0);
+        assert!(
+            (best[0] - 1.0).abs() < 0.1 && (best[1] - 1.0).abs() < 0.1,
+            "{:?}",
+            best
+        );
+    }
+
+    #[test]
+    fn test_constrained_differential_evolution() {
+        // Minimize the sphere subject to x0 + x1 + x2 >= 3, optimum at (1, 1, 1)
+        let constraints = |x: &Vec<f64>| vec![inequality(3.0 - x.iter().sum::<f64>())];
+
+        let mut de = DifferentialEvolution::new(random_population(30, 3, 6), Sphere)
+            .with_constraints(Constrained::new(constraints, FeasibilityRules))
+            .with_seed(6);
+        de.run(500);
+
+        let best = de.best_individual();
+        assert!(best.iter().sum::<f64>() >= 3.0);
+        assert!(best.iter().all(|v| (v - 1.0).abs() < 1e-3), "{:?}", best);
+    }
+
+    #[test]
+    fn test_constrained_particle_swarm() {
+        // Minimize the sphere subject to x0 + x1 + x2 >= 3, optimum at (1, 1, 1)
+        let constraints = |x: &Vec<f64>| vec![inequality(3.0 - x.iter().sum::<f64>())];
+
+        let mut pso = ParticleSwarm::new(random_population(30, 3, 7), Sphere)
+            .with_constraints(Constrained::new(constraints, FeasibilityRules))
+            .with_seed(7);
+        pso.run(500);
+
+        let best = pso.best_individual();
+        assert!(best.iter().sum::<f64>() >= 3.0);
+        assert!(best.iter().all(|v| (v - 1.0).abs() < 0.05), "{:?}", best);
+    }
+
+    #[test]
+    fn test_constrained_cma_es() {
+        // Minimize the sphere subject to x0 + x1 + x2 = 3, optimum at (1, 1, 1)
+        let objective = Minimize(|x: &Vec<f64>| -> f64 { x.iter().map(|v| v * v).sum() });
+        let constraints = |x: &Vec<f64>| vec![equality(x.iter().sum::<f64>() - 3.0, 1e-4)];
+
+        let mut strategy = CmaEs::new(vec![0.0; 3], 1.0, objective)
+            .with_constraints(Constrained::new(constraints, EpsilonConstraint::new(100)))
+            .with_seed(3);
+        strategy.run(400);
+
+        for value in strategy.best_individual() {
+            assert!(
+                (value - 1.0).abs() < 0.01,
+                "Gene did not converge: {}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_violations_are_computed_once_per_candidate() {
+        let calls = Cell::new(0);
+        let constraints = |x: &Vec<f64>| {
+            calls.set(calls.get() + 1);
+            vec![inequality(2.0 - x[0] - x[1])]
+        };
+
+        let mut strategy = CmaEs::new(vec![0.0, 0.0], 1.0, Sphere)
+            .with_constraints(Constrained::new(constraints, FeasibilityRules))
+            .with_seed(5);
+        strategy.run(10);
+        assert_eq!(calls.get(), strategy.evaluations());
+
+        calls.set(0);
+        let mut strategy =
+            OnePlusOneStrategy::new(vec![0.0, 0.0], GaussianMutator::new(1.0, 0.1), Sphere)
+                .with_constraints(Constrained::new(constraints, FeasibilityRules))
+                .with_seed(5);
+        strategy.run(10);
+        assert_eq!(calls.get(), strategy.evaluations());
+    }
+}
@@ -0,0 +1,700 @@
+// Module for differential evolution
+//
+// Every generation each target vector x_i is challenged by a trial vector. The
+// trial is built from a mutant, a sum of scaled difference vectors of other
+// population members, which is crossed over with x_i and replaces it if it is
+// at least as good. The classic variants use a fixed scale factor F and
+// crossover rate CR. JADE and SHADE mutate with current-to-pbest/1 and an
+// archive of replaced parents, and sample F and CR per individual around
+// parameter memories that learn from successful trials.
+//
+// With ask/tell the first ask returns the initial population and every later
+// ask the trial vectors of one generation.
+//
+// Under constraints the trials and their targets are ranked together once per
+// generation, and a trial replaces its target if it ranks ahead of it.
+
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Cauchy, Distribution, Normal};
+
+use crate::bounds::Bounds;
+use crate::constraints::{Ranking, Unconstrained};
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+use crate::optimizer::{check_tell, AskTell, Optimizer, TellError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeVariant {
+    // v = x_r1 + F (x_r2 - x_r3), binomial crossover
+    Rand1Bin,
+    // v = x_best + F (x_r1 - x_r2), binomial crossover
+    Best1Bin,
+    // v = x_i + F (x_best - x_i) + F (x_r1 - x_r2), binomial crossover
+    CurrentToBest1Bin,
+    // v = x_r1 + F (x_r2 - x_r3) + F (x_r4 - x_r5), exponential crossover
+    Rand2Exp,
+    // Adaptive DE (Zhang and Sanderson) with learning rate `c` (0.1) and
+    // greediness `p` (0.05) of current-to-pbest/1
+    Jade { c: f64, p: f64 },
+    // Success-history based adaptive DE (Tanabe and Fukunaga) with
+    // `memory_size` entries per parameter memory
+    Shade { memory_size: usize },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DifferentialEvolution<G, O, K = Unconstrained> {
+    population: Vec<G>,
+    fitness: Vec<f64>,
+    violations: Vec<f64>,
+    // Population indices ordered best first
+    order: Vec<usize>,
+    objective: O,
+    ranking: K,
+    variant: DeVariant,
+    f: f64,
+    cr: f64,
+    // Parameter memories, a single entry for JADE
+    memory_f: Vec<f64>,
+    memory_cr: Vec<f64>,
+    memory_index: usize,
+    archive: Vec<G>,
+    bounds: Option<Bounds>,
+    // Trials with their F and CR, and the points at which they are evaluated
+    trials: Vec<(G, f64, f64)>,
+    candidates: Vec<G>,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>> DifferentialEvolution<G, O> {
+    // DE/rand/1/bin with F = 0.5 and CR = 0.9
+    pub fn new(initial_population: Vec<G>, objective: O) -> Self {
+        assert!(
+            initial_population.len() >= 4,
+            "Differential evolution needs at least four individuals"
+        );
+        let dimension = initial_population[0].dimension();
+        assert!(
+            initial_population
+                .iter()
+                .all(|x| x.dimension() == dimension),
+            "All individuals must have the same dimension"
+        );
+
+        DifferentialEvolution {
+            population: initial_population,
+            fitness: Vec::new(),
+            violations: Vec::new(),
+            order: Vec::new(),
+            objective,
+            ranking: Unconstrained,
+            variant: DeVariant::Rand1Bin,
+            f: 0.5,
+            cr: 0.9,
+            memory_f: vec![0.5],
+            memory_cr: vec![0.5],
+            memory_index: 0,
+            archive: Vec::new(),
+            bounds: None,
+            trials: Vec::new(),
+            candidates: Vec::new(),
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> DifferentialEvolution<G, O, K> {
+    // Compares trials and targets under constraints, see `crate::constraints`
+    pub fn with_constraints<K2: Ranking<G>>(self, ranking: K2) -> DifferentialEvolution<G, O, K2> {
+        DifferentialEvolution {
+            population: self.population,
+            fitness: self.fitness,
+            violations: self.violations,
+            order: self.order,
+            objective: self.objective,
+            ranking,
+            variant: self.variant,
+            f: self.f,
+            cr: self.cr,
+            memory_f: self.memory_f,
+            memory_cr: self.memory_cr,
+            memory_index: self.memory_index,
+            archive: self.archive,
+            bounds: self.bounds,
+            trials: self.trials,
+            candidates: self.candidates,
+            rng: self.rng,
+            generation: self.generation,
+            evaluations: self.evaluations,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn with_variant(mut self, variant: DeVariant) -> Self {
+        match variant {
+            DeVariant::Rand2Exp => assert!(
+                self.population.len() >= 6,
+                "DE/rand/2 needs at least six individuals"
+            ),
+            DeVariant::Jade { c, p } => {
+                assert!(c > 0.0 && c <= 1.0, "The learning rate must be in (0, 1]");
+                assert!(p > 0.0 && p <= 1.0, "p must be in (0, 1]");
+            }
+            DeVariant::Shade { memory_size } => {
+                assert!(memory_size > 0, "The memory must not be empty")
+            }
+            _ => {}
+        }
+
+        let memory_size = match variant {
+            DeVariant::Shade { memory_size } => memory_size,
+            _ => 1,
+        };
+        self.variant = variant;
+        self.memory_f = vec![0.5; memory_size];
+        self.memory_cr = vec![0.5; memory_size];
+        self.memory_index = 0;
+        self
+    }
+
+    // Scale factor and crossover rate of the classic variants
+    pub fn with_parameters(mut self, f: f64, cr: f64) -> Self {
+        assert!(f > 0.0, "The scale factor must be positive");
+        assert!(
+            (0.0..=1.0).contains(&cr),
+            "The crossover rate must be a probability"
+        );
+        self.f = f;
+        self.cr = cr;
+        self
+    }
+
+    // Every trial vector is repaired (or penalized) according to the bounds
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        assert_eq!(
+            bounds.dimension(),
+            self.population[0].dimension(),
+            "The bounds do not match the dimension of the search space"
+        );
+        self.bounds = Some(bounds);
+        self
+    }
+
+    fn evaluation_point(&self, individual: &G) -> G {
+        match &self.bounds {
+            Some(bounds) => bounds.evaluation_point(individual),
+            None => individual.clone(),
+        }
+    }
+
+    fn is_adaptive(&self) -> bool {
+        matches!(
+            self.variant,
+            DeVariant::Jade { .. } | DeVariant::Shade { .. }
+        )
+    }
+
+    // Draws F and CR for one trial, around memory entry `slot` for the adaptive variants
+    fn sample_parameters(&mut self, slot: usize) -> (f64, f64) {
+        if !self.is_adaptive() {
+            return (self.f, self.cr);
+        }
+
+        let cr = Normal::new(self.memory_cr[slot], 0.1)
+            .unwrap()
+            .sample(&mut self.rng)
+            .clamp(0.0, 1.0);
+        let cauchy = Cauchy::new(self.memory_f[slot], 0.1).unwrap();
+        let f = loop {
+            let f: f64 = cauchy.sample(&mut self.rng);
+            if f > 0.0 {
+                break f.min(1.0);
+            }
+        };
+        (f, cr)
+    }
+
+    // `count` distinct population indices different from `exclude`
+    fn distinct(&mut self, count: usize, exclude: usize) -> Vec<usize> {
+        sample(&mut self.rng, self.population.len() - 1, count)
+            .into_iter()
+            .map(|index| if index >= exclude { index + 1 } else { index })
+            .collect()
+    }
+
+    fn mutant(&mut self, target: usize, f: f64, order: &[usize]) -> Vec<f64> {
+        let genes = |population: &[G], index: usize| population[index].genes().to_vec();
+        let x = genes(&self.population, target);
+        let best = genes(&self.population, order[0]);
+
+        let combine = |base: &[f64], terms: &[(&[f64], &[f64])]| -> Vec<f64> {
+            (0..base.len())
+                .map(|j| base[j] + terms.iter().map(|(a, b)| f * (a[j] - b[j])).sum::<f64>())
+                .collect()
+        };
+
+        match self.variant {
+            DeVariant::Rand1Bin => {
+                let r = self.distinct(3, target);
+                let [a, b, c] = [0, 1, 2].map(|k| genes(&self.population, r[k]));
+                combine(&a, &[(&b, &c)])
+            }
+            DeVariant::Best1Bin => {
+                let r = self.distinct(2, target);
+                let [a, b] = [0, 1].map(|k| genes(&self.population, r[k]));
+                combine(&best, &[(&a, &b)])
+            }
+            DeVariant::CurrentToBest1Bin => {
+                let r = self.distinct(2, target);
+                let [a, b] = [0, 1].map(|k| genes(&self.population, r[k]));
+                combine(&x, &[(&best, &x), (&a, &b)])
+            }
+            DeVariant::Rand2Exp => {
+                let r = self.distinct(5, target);
+                let [a, b, c, d, e] = [0, 1, 2, 3, 4].map(|k| genes(&self.population, r[k]));
+                combine(&a, &[(&b, &c), (&d, &e)])
+            }
+            DeVariant::Jade { p, .. } => self.current_to_pbest(target, &x, f, p, order),
+            DeVariant::Shade { .. } => {
+                let n = self.population.len() as f64;
+                let p = self.rng.gen_range((2.0 / n).min(0.2)..=0.2);
+                self.current_to_pbest(target, &x, f, p, order)
+            }
+        }
+    }
+
+    // v = x_i + F (x_pbest - x_i) + F (x_r1 - x_r2), where x_pbest is one of
+    // the best p * NP individuals and x_r2 may also come from the archive
+    fn current_to_pbest(
+        &mut self,
+        target: usize,
+        x: &[f64],
+        f: f64,
+        p: f64,
+        order: &[usize],
+    ) -> Vec<f64> {
+        let n = self.population.len();
+        let top = ((p * n as f64).round() as usize).clamp(1, n);
+        let pbest = order[self.rng.gen_range(0..top)];
+        let r1 = self.distinct(1, target)[0];
+        let r2 = loop {
+            let candidate = self.rng.gen_range(0..n + self.archive.len());
+            if candidate != target && candidate != r1 {
+                break candidate;
+            }
+        };
+        let pbest = self.population[pbest].genes();
+        let a = self.population[r1].genes();
+        let b = if r2 < n {
+            self.population[r2].genes()
+        } else {
+            self.archive[r2 - n].genes()
+        };
+
+        (0..x.len())
+            .map(|j| x[j] + f * (pbest[j] - x[j]) + f * (a[j] - b[j]))
+            .collect()
+    }
+
+    fn crossover(&mut self, target: usize, mutant: &[f64], cr: f64) -> G {
+        let mut trial = self.population[target].clone();
+        let n = mutant.len();
+        let genes = trial.genes_mut();
+        let start = self.rng.gen_range(0..n);
+
+        if let DeVariant::Rand2Exp = self.variant {
+            // Copy a run of consecutive genes, wrapping around, from the mutant
+            let mut j = start;
+            for _ in 0..n {
+                genes[j] = mutant[j];
+                j = (j + 1) % n;
+                if self.rng.gen::<f64>() >= cr {
+                    break;
+                }
+            }
+        } else {
+            // At least the gene at `start` comes from the mutant
+            for j in 0..n {
+                if j == start || self.rng.gen::<f64>() < cr {
+                    genes[j] = mutant[j];
+                }
+            }
+        }
+        trial
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        if self.fitness.is_empty() {
+            self.evaluate_candidates(on_evaluation);
+        }
+        self.evaluate_candidates(on_evaluation);
+    }
+
+    fn evaluate_candidates(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.objective.evaluate_batch(&self.candidates);
+        for (individual, fitness) in self.candidates.iter().zip(&fitness) {
+            on_evaluation(individual, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    pub fn ask(&mut self) -> &[G] {
+        if !self.candidates.is_empty() {
+            return &self.candidates;
+        }
+        if self.fitness.is_empty() {
+            if let Some(bounds) = &self.bounds {
+                for individual in self.population.iter_mut() {
+                    bounds.repair(individual, &mut self.rng);
+                }
+            }
+            self.candidates = (0..self.population.len())
+                .map(|i| self.evaluation_point(&self.population[i]))
+                .collect();
+            return &self.candidates;
+        }
+
+        let order = self.order.clone();
+        for i in 0..self.population.len() {
+            let slot = self.rng.gen_range(0..self.memory_f.len());
+            let (f, cr) = self.sample_parameters(slot);
+            let mutant = self.mutant(i, f, &order);
+            let mut trial = self.crossover(i, &mutant, cr);
+            if let Some(bounds) = &self.bounds {
+                bounds.repair(&mut trial, &mut self.rng);
+            }
+            self.candidates.push(self.evaluation_point(&trial));
+            self.trials.push((trial, f, cr));
+        }
+        &self.candidates
+    }
+
+    // Telling the fitness of the initial population does not count as a generation
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.candidates, fitness)?;
+        self.candidates.clear();
+        self.evaluations += fitness.len();
+        let direction = self.objective.direction();
+        let penalize = |fitness: f64, individual: &G| match &self.bounds {
+            Some(bounds) => bounds.penalize(fitness, individual, direction),
+            None => fitness,
+        };
+
+        if self.fitness.is_empty() {
+            self.fitness = self
+                .population
+                .iter()
+                .zip(fitness)
+                .map(|(individual, fitness)| penalize(*fitness, individual))
+                .collect();
+            self.violations = self.ranking.violations(&self.population);
+            self.order =
+                self.ranking
+                    .rank(&self.fitness, &self.violations, direction, &mut self.rng);
+            return Ok(());
+        }
+
+        let (trials, parameters): (Vec<G>, Vec<(f64, f64)>) = self
+            .trials
+            .drain(..)
+            .map(|(trial, f, cr)| (trial, (f, cr)))
+            .unzip();
+        let trial_fitness: Vec<f64> = trials
+            .iter()
+            .zip(fitness)
+            .map(|(trial, fitness)| penalize(*fitness, trial))
+            .collect();
+        let trial_violations = self.ranking.violations(&trials);
+
+        // Trials come first, so that ties are accepted and the population can
+        // drift across plateaus
+        let n = self.population.len();
+        let combined_fitness = [trial_fitness.as_slice(), &self.fitness].concat();
+        let combined_violations = [trial_violations.as_slice(), &self.violations].concat();
+        let mut position = vec![0; 2 * n];
+        let order = self.ranking.rank(
+            &combined_fitness,
+            &combined_violations,
+            direction,
+            &mut self.rng,
+        );
+        for (rank, index) in order.into_iter().enumerate() {
+            position[index] = rank;
+        }
+
+        let mut successful_f = Vec::new();
+        let mut successful_cr = Vec::new();
+        let mut improvements = Vec::new();
+        for (i, (trial, (f, cr))) in trials.into_iter().zip(parameters).enumerate() {
+            let (fitness, violation) = (trial_fitness[i], trial_violations[i]);
+            if position[i] < position[n + i] {
+                let improved = violation < self.violations[i]
+                    || (violation == self.violations[i]
+                        && direction.is_better(fitness, self.fitness[i]));
+                if improved {
+                    successful_f.push(f);
+                    successful_cr.push(cr);
+                    improvements.push((fitness - self.fitness[i]).abs());
+                    if self.is_adaptive() {
+                        self.archive.push(self.population[i].clone());
+                    }
+                }
+                self.population[i] = trial;
+                self.fitness[i] = fitness;
+                self.violations[i] = violation;
+            } else {
+                position[i] = position[n + i];
+            }
+        }
+        self.order = (0..n).collect();
+        self.order.sort_by_key(|i| position[*i]);
+
+        self.adapt(&successful_f, &successful_cr, &improvements);
+        self.generation += 1;
+        Ok(())
+    }
+
+    fn adapt(&mut self, successful_f: &[f64], successful_cr: &[f64], improvements: &[f64]) {
+        // The archive keeps at most NP randomly chosen replaced parents
+        while self.archive.len() > self.population.len() {
+            let index = self.rng.gen_range(0..self.archive.len());
+            self.archive.swap_remove(index);
+        }
+        if successful_f.is_empty() {
+            return;
+        }
+
+        match self.variant {
+            DeVariant::Jade { c, .. } => {
+                let weights = vec![1.0; successful_f.len()];
+                self.memory_cr[0] =
+                    (1.0 - c) * self.memory_cr[0] + c * weighted_mean(successful_cr, &weights);
+                self.memory_f[0] =
+                    (1.0 - c) * self.memory_f[0] + c * lehmer_mean(successful_f, &weights);
+            }
+            DeVariant::Shade { .. } => {
+                let k = self.memory_index;
+                self.memory_cr[k] = weighted_mean(successful_cr, improvements);
+                self.memory_f[k] = lehmer_mean(successful_f, improvements);
+                self.memory_index = (k + 1) % self.memory_f.len();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    pub fn population(&self) -> &[G] {
+        &self.population
+    }
+
+    pub fn variant(&self) -> DeVariant {
+        self.variant
+    }
+
+    // The best individual of the current population, which is the best one
+    // found so far since every slot only ever improves
+    pub fn best_individual(&self) -> &G {
+        if self.fitness.is_empty() {
+            return &self.population[0];
+        }
+        &self.population[self.order[0]]
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        if self.fitness.is_empty() {
+            return None;
+        }
+        Some(self.fitness[self.order[0]])
+    }
+
+    pub fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+fn weighted_mean(values: &[f64], weights: &[f64]) -> f64 {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return values.iter().sum::<f64>() / values.len() as f64;
+    }
+    values.iter().zip(weights).map(|(v, w)| v * w).sum::<f64>() / total
+}
+
+// Weighted Lehmer mean sum(w x^2) / sum(w x), which favors larger scale factors
+fn lehmer_mean(values: &[f64], weights: &[f64]) -> f64 {
+    let weights: Vec<f64> = if weights.iter().sum::<f64>() > 0.0 {
+        weights.to_vec()
+    } else {
+        vec![1.0; values.len()]
+    };
+    let numerator: f64 = values.iter().zip(&weights).map(|(v, w)| w * v * v).sum();
+    let denominator: f64 = values.iter().zip(&weights).map(|(v, w)| w * v).sum();
+    numerator / denominator
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> Optimizer<G>
+    for DifferentialEvolution<G, O, K>
+{
+    fn step(&mut self) {
+        DifferentialEvolution::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        DifferentialEvolution::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        DifferentialEvolution::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        DifferentialEvolution::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        DifferentialEvolution::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        DifferentialEvolution::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        DifferentialEvolution::population(self)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        &self.fitness
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        DifferentialEvolution::direction(self)
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> AskTell<G>
+    for DifferentialEvolution<G, O, K>
+{
+    fn ask(&mut self) -> &[G] {
+        DifferentialEvolution::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        DifferentialEvolution::tell(self, fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Rosenbrock, Sphere};
+    use crate::testing::random_population;
+
+    #[test]
+    fn test_classic_variants_solve_sphere() {
+        for variant in [
+            DeVariant::Rand1Bin,
+            DeVariant::Best1Bin,
+            DeVariant::CurrentToBest1Bin,
+            DeVariant::Rand2Exp,
+        ] {
+            let mut de = DifferentialEvolution::new(random_population(30, 5, 1), Sphere)
+                .with_variant(variant)
+                .with_seed(1);
+            de.run(300);
+
+            assert!(
+                de.best_fitness().unwrap() < 1e-6,
+                "{:?} did not converge: {:?}",
+                variant,
+                de.best_fitness()
+            );
+        }
+    }
+
+    #[test]
+    fn test_adaptive_variants_solve_rosenbrock() {
+        for variant in [
+            DeVariant::Jade { c: 0.1, p: 0.05 },
+            DeVariant::Shade { memory_size: 10 },
+        ] {
+            let mut de = DifferentialEvolution::new(random_population(40, 5, 2), Rosenbrock)
+                .with_variant(variant)
+                .with_seed(2);
+            de.run(1500);
+
+            assert!(
+                de.best_fitness().unwrap() < 1e-6,
+                "{:?} did not converge: {:?}",
+                variant,
+                de.best_fitness()
+            );
+            assert_eq!(de.evaluations(), 40 * 1501);
+        }
+    }
+
+    #[test]
+    fn test_differential_evolution_respects_bounds() {
+        // The unconstrained optimum lies outside the box, the constrained one at (1, 1, 1)
+        let bounds = Bounds::uniform(3, 1.0, 2.0);
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let population = (0..10).map(|_| bounds.sample(&mut rng)).collect();
+
+        let mut de = DifferentialEvolution::new(population, Sphere)
+            .with_bounds(bounds.clone())
+            .with_seed(3);
+        de.run(200);
+
+        assert!(de.population().iter().all(|x| bounds.contains(x)));
+        assert!(de.best_individual().iter().all(|v| (v - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_differential_evolution_repairs_initial_population() {
+        let bounds = Bounds::uniform(3, 1.0, 2.0);
+        let population: Vec<Vec<f64>> = random_population(10, 3, 4)
+            .into_iter()
+            .map(|x| x.iter().map(|v| v * 10.0).collect())
+            .collect();
+        assert!(population.iter().any(|x| !bounds.contains(x)));
+
+        let mut de = DifferentialEvolution::new(population, Sphere)
+            .with_bounds(bounds.clone())
+            .with_seed(4);
+        de.ask();
+        assert!(de.population().iter().all(|x| bounds.contains(x)));
+
+        de.run(200);
+        assert!(de.population().iter().all(|x| bounds.contains(x)));
+        assert!(de.best_individual().iter().all(|v| (v - 1.0).abs() < 1e-6));
+    }
+}
@@ -0,0 +1,238 @@
+// Evaluated individuals, fitness caching and evaluation counting
+//
+// `Evaluated` keeps a genome together with its fitness, so an individual that
+// survives into the next generation is never evaluated again. `Cached`
+// memoizes an objective for duplicate genomes, which are common in discrete
+// search spaces and after recombination of similar parents. `Counted` counts
+// the evaluations that actually reach the wrapped objective, e.g. behind a
+// cache, through a counter handle that stays with the caller.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evaluated<G> {
+    pub genome: G,
+    pub fitness: f64,
+}
+
+impl<G> Evaluated<G> {
+    pub fn new(genome: G, fitness: f64) -> Self {
+        Evaluated { genome, fitness }
+    }
+
+    // Returns true if this individual is strictly better than `other`
+    pub fn is_better_than(&self, other: &Evaluated<G>, direction: OptimizationDirection) -> bool {
+        direction.is_better(self.fitness, other.fitness)
+    }
+}
+
+// Genes that can be part of a cache key. Floats are compared by their bit
+// pattern, so 0.0 and -0.0 are different keys.
+pub trait KeyGene {
+    fn key(&self) -> u64;
+}
+
+macro_rules! impl_key_gene {
+    ($($gene:ty => $key:expr),*) => {
+        $(
+            impl KeyGene for $gene {
+                fn key(&self) -> u64 {
+                    $key(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_key_gene!(f64 => f64::to_bits, i64 => |gene: i64| gene as u64, bool => u64::from);
+
+fn cache_key<G: Genome>(individual: &G) -> Vec<u64>
+where
+    G::Gene: KeyGene,
+{
+    individual.genes().iter().map(KeyGene::key).collect()
+}
+
+// Remembers the fitness of every genome it has seen. The objective must be
+// deterministic for the cache to be correct. The cache grows without bound,
+// call `clear` to release it.
+pub struct Cached<O> {
+    inner: O,
+    cache: HashMap<Vec<u64>, f64>,
+    hits: usize,
+}
+
+impl<O> Cached<O> {
+    pub fn new(inner: O) -> Self {
+        Cached {
+            inner,
+            cache: HashMap::new(),
+            hits: 0,
+        }
+    }
+
+    // Number of evaluations answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    // Number of distinct genomes in the cache
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+impl<G: Genome, O: Objective<G>> Objective<G> for Cached<O>
+where
+    G::Gene: KeyGene,
+{
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        let key = cache_key(individual);
+        if let Some(fitness) = self.cache.get(&key) {
+            self.hits += 1;
+            return *fitness;
+        }
+        let fitness = self.inner.evaluate(individual);
+        self.cache.insert(key, fitness);
+        fitness
+    }
+
+    // Only genomes that are neither cached nor repeated within the batch are
+    // passed on, as one batch
+    fn evaluate_batch(&mut self, individuals: &[G]) -> Vec<f64> {
+        let keys: Vec<Vec<u64>> = individuals.iter().map(cache_key).collect();
+        let mut missing = Vec::new();
+        let mut seen = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            if !self.cache.contains_key(key) && seen.insert(key, i).is_none() {
+                missing.push(i);
+            }
+        }
+        self.hits += individuals.len() - missing.len();
+
+        let batch: Vec<G> = missing.iter().map(|i| individuals[*i].clone()).collect();
+        for (i, fitness) in missing.iter().zip(self.inner.evaluate_batch(&batch)) {
+            self.cache.insert(keys[*i].clone(), fitness);
+        }
+        keys.iter().map(|key| self.cache[key]).collect()
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.inner.direction()
+    }
+}
+
+// Shared handle to the count of a `Counted` objective. It can be read while
+// the objective is owned by a strategy.
+#[derive(Debug, Clone, Default)]
+pub struct EvaluationCounter(Arc<AtomicUsize>);
+
+impl EvaluationCounter {
+    pub fn new() -> Self {
+        EvaluationCounter::default()
+    }
+
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn add(&self, evaluations: usize) {
+        self.0.fetch_add(evaluations, Ordering::Relaxed);
+    }
+}
+
+pub struct Counted<O> {
+    inner: O,
+    counter: EvaluationCounter,
+}
+
+impl<O> Counted<O> {
+    pub fn new(inner: O, counter: EvaluationCounter) -> Self {
+        Counted { inner, counter }
+    }
+}
+
+impl<G, O: Objective<G>> Objective<G> for Counted<O> {
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        self.counter.add(1);
+        self.inner.evaluate(individual)
+    }
+
+    fn evaluate_batch(&mut self, individuals: &[G]) -> Vec<f64> {
+        self.counter.add(individuals.len());
+        self.inner.evaluate_batch(individuals)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.inner.direction()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution_strategies::{BitFlipMutator, SimpleSelector};
+    use crate::genetic_algorithms::{GeneticAlgorithm, UniformCrossover};
+    use crate::genome::BitString;
+
+    #[test]
+    fn test_cached_objective() {
+        let counter = EvaluationCounter::new();
+        let mut objective = Cached::new(Counted::new(
+            |x: &Vec<f64>| x.iter().sum::<f64>(),
+            counter.clone(),
+        ));
+
+        assert_eq!(objective.evaluate(&vec![1.0, 2.0]), 3.0);
+        assert_eq!(objective.evaluate(&vec![1.0, 2.0]), 3.0);
+        assert_eq!(counter.get(), 1);
+
+        // Duplicates within a batch are evaluated once
+        let batch = vec![
+            vec![1.0, 2.0],
+            vec![0.5, 0.5],
+            vec![0.5, 0.5],
+            vec![4.0, 0.0],
+        ];
+        assert_eq!(objective.evaluate_batch(&batch), vec![3.0, 1.0, 1.0, 4.0]);
+        assert_eq!(counter.get(), 3);
+        assert_eq!(objective.hits(), 3);
+        assert_eq!(objective.len(), 3);
+
+        objective.clear();
+        objective.evaluate(&vec![1.0, 2.0]);
+        assert_eq!(counter.get(), 4);
+    }
+
+    #[test]
+    fn test_cache_saves_evaluations() {
+        // Once the population has converged most offspring are duplicates
+        let one_max = |x: &BitString| -> f64 { x.iter().filter(|bit| **bit).count() as f64 };
+        let counter = EvaluationCounter::new();
+        let mut ga = GeneticAlgorithm::new(
+            vec![vec![false; 16]; 20],
+            UniformCrossover::default(),
+            BitFlipMutator::new(1.0 / 16.0),
+            SimpleSelector::new(10, Cached::new(Counted::new(one_max, counter.clone()))),
+        )
+        .with_seed(3);
+        ga.run(100);
+
+        assert_eq!(ga.best_fitness(), Some(16.0));
+        assert!(counter.get() < ga.evaluations() / 2);
+    }
+}
@@ -0,0 +1,308 @@
+// (1+1) evolution strategy with Rechenberg's 1/5th success rule
+//
+// The ratio of successful mutations is measured over a window of generations.
+// If more than a fifth of the mutations were successful the step size is
+// increased, if fewer were successful it is decreased.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::Mutate;
+use crate::constraints::{Ranking, Unconstrained};
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+use crate::optimizer::{check_tell, AskTell, Optimizer, TellError};
+
+// Mutators whose step size can be controlled by a strategy
+pub trait AdaptiveMutate<G>: Mutate<G> {
+    fn step_size(&self) -> f64;
+    fn set_step_size(&mut self, step_size: f64);
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveOnePlusOneStrategy<G, M, O, K = Unconstrained> {
+    individual: G,
+    // Fitness and constraint violation of `individual`, known after the first
+    // generation
+    fitness: Option<f64>,
+    violation: f64,
+    pending: Vec<G>,
+    mutator: M,
+    objective: O,
+    ranking: K,
+    window: usize,
+    adaptation_factor: f64,
+    trials: usize,
+    successes: usize,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
+}
+
+impl<G: Genome, M: AdaptiveMutate<G>, O: Objective<G>> AdaptiveOnePlusOneStrategy<G, M, O> {
+    pub fn new(initial_value: G, mutator: M, objective: O) -> Self {
+        // Schwefel's recommendation: adapt every 10 * n generations by a factor of 0.85
+        let window = 10 * initial_value.dimension().max(1);
+        AdaptiveOnePlusOneStrategy {
+            individual: initial_value,
+            fitness: None,
+            violation: 0.0,
+            pending: Vec::new(),
+            mutator,
+            objective,
+            ranking: Unconstrained,
+            window,
+            adaptation_factor: 0.85,
+            trials: 0,
+            successes: 0,
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+}
+
+impl<G: Genome, M: AdaptiveMutate<G>, O: Objective<G>, K: Ranking<G>>
+    AdaptiveOnePlusOneStrategy<G, M, O, K>
+{
+    // Compares parent and offspring under constraints, see `crate::constraints`.
+    // A mutation counts as a success if the offspring replaces the parent.
+    pub fn with_constraints<K2: Ranking<G>>(
+        self,
+        ranking: K2,
+    ) -> AdaptiveOnePlusOneStrategy<G, M, O, K2> {
+        AdaptiveOnePlusOneStrategy {
+            individual: self.individual,
+            fitness: self.fitness,
+            violation: self.violation,
+            pending: self.pending,
+            mutator: self.mutator,
+            objective: self.objective,
+            ranking,
+            window: self.window,
+            adaptation_factor: self.adaptation_factor,
+            trials: self.trials,
+            successes: self.successes,
+            rng: self.rng,
+            generation: self.generation,
+            evaluations: self.evaluations,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn with_window(mut self, window: usize) -> Self {
+        assert!(window > 0, "The adaptation window must not be empty");
+        self.window = window;
+        self
+    }
+
+    pub fn with_adaptation_factor(mut self, adaptation_factor: f64) -> Self {
+        assert!(
+            adaptation_factor > 0.0 && adaptation_factor < 1.0,
+            "The adaptation factor must be in (0, 1)"
+        );
+        self.adaptation_factor = adaptation_factor;
+        self
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.objective.evaluate_batch(&self.pending);
+        for (individual, fitness) in self.pending.iter().zip(&fitness) {
+            on_evaluation(individual, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    // The offspring of the next generation, preceded by the parent in the
+    // first generation
+    pub fn ask(&mut self) -> &[G] {
+        if self.pending.is_empty() {
+            if self.fitness.is_none() {
+                self.pending.push(self.individual.clone());
+            }
+            let mut offspring = self.individual.clone();
+            self.mutator.mutate(&mut offspring, &mut self.rng);
+            self.pending.push(offspring);
+        }
+        &self.pending
+    }
+
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.pending, fitness)?;
+        let violations = self.ranking.violations(&self.pending);
+        let offspring_fitness = fitness[fitness.len() - 1];
+        let offspring_violation = violations[violations.len() - 1];
+        let (parent_fitness, parent_violation) = match self.fitness {
+            Some(fitness) => (fitness, self.violation),
+            None => (fitness[0], violations[0]),
+        };
+        let offspring = self.pending.pop().unwrap();
+        self.pending.clear();
+
+        // The offspring replaces the parent only if it ranks strictly better
+        let order = self.ranking.rank(
+            &[parent_fitness, offspring_fitness],
+            &[parent_violation, offspring_violation],
+            self.objective.direction(),
+            &mut self.rng,
+        );
+        if order[0] == 1 {
+            self.individual = offspring;
+            self.fitness = Some(offspring_fitness);
+            self.violation = offspring_violation;
+            self.successes += 1;
+        } else {
+            self.fitness = Some(parent_fitness);
+            self.violation = parent_violation;
+        }
+        self.evaluations += fitness.len();
+        self.generation += 1;
+
+        self.trials += 1;
+        if self.trials == self.window {
+            self.adapt_step_size();
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    fn adapt_step_size(&mut self) {
+        let success_ratio = self.successes as f64 / self.trials as f64;
+        let step_size = self.mutator.step_size();
+        if success_ratio > 0.2 {
+            self.mutator
+                .set_step_size(step_size / self.adaptation_factor);
+        } else if success_ratio < 0.2 {
+            self.mutator
+                .set_step_size(step_size * self.adaptation_factor);
+        }
+        self.trials = 0;
+        self.successes = 0;
+    }
+
+    pub fn best_individual(&self) -> &G {
+        &self.individual
+    }
+
+    // Fitness of the current individual, known once the first generation has
+    // evaluated the starting point
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.fitness
+    }
+
+    pub fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    // The current, adapted step size of the mutator
+    pub fn step_size(&self) -> f64 {
+        self.mutator.step_size()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+impl<G: Genome, M: AdaptiveMutate<G>, O: Objective<G>, K: Ranking<G>> Optimizer<G>
+    for AdaptiveOnePlusOneStrategy<G, M, O, K>
+{
+    fn step(&mut self) {
+        AdaptiveOnePlusOneStrategy::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        AdaptiveOnePlusOneStrategy::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        AdaptiveOnePlusOneStrategy::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        AdaptiveOnePlusOneStrategy::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        AdaptiveOnePlusOneStrategy::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        AdaptiveOnePlusOneStrategy::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        std::slice::from_ref(&self.individual)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        self.fitness.as_slice()
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        AdaptiveOnePlusOneStrategy::direction(self)
+    }
+
+    fn step_size(&self) -> Option<f64> {
+        Some(AdaptiveOnePlusOneStrategy::step_size(self))
+    }
+}
+
+impl<G: Genome, M: AdaptiveMutate<G>, O: Objective<G>, K: Ranking<G>> AskTell<G>
+    for AdaptiveOnePlusOneStrategy<G, M, O, K>
+{
+    fn ask(&mut self) -> &[G] {
+        AdaptiveOnePlusOneStrategy::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        AdaptiveOnePlusOneStrategy::tell(self, fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere, Transformed};
+    use crate::evolution_strategies::SimpleMutator;
+    use crate::objective::Minimize;
+
+    #[test]
+    fn test_one_fifth_rule_shifted_sphere() {
+        let benchmark = Transformed::new(Sphere).with_shift(vec![2.0]);
+        let objective = Minimize(|x: &f64| benchmark.value(&[*x]));
+        let mutator = SimpleMutator::new(1.0, 1.0);
+
+        let mut strategy = AdaptiveOnePlusOneStrategy::new(100.0, mutator, objective)
+            .with_window(5)
+            .with_seed(1);
+        strategy.run(1000);
+
+        assert!(
+            (strategy.best_individual() - 2.0).abs() < 1e-4,
+            "Did not converge: {}",
+            strategy.best_individual()
+        );
+        // Close to the optimum the step size must have shrunk considerably
+        assert!(strategy.step_size() < 1e-2);
+    }
+}
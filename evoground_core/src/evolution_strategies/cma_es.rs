@@ -0,0 +1,636 @@
+// Covariance matrix adaptation evolution strategy (CMA-ES)
+//
+// Follows "The CMA Evolution Strategy: A Tutorial" by N. Hansen. Offspring are
+// sampled from a multivariate normal distribution N(m, sigma^2 C). The mean is
+// moved by weighted recombination of the best mu offspring, the global step
+// size sigma is controlled by cumulative step-size adaptation and C is updated
+// with the rank-one and rank-mu updates.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, StandardNormal};
+
+use crate::bounds::Bounds;
+use crate::constraints::{Ranking, Unconstrained};
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+use crate::optimizer::{check_tell, AskTell, Optimizer, TellError};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CmaEs<G, O, K = Unconstrained> {
+    objective: O,
+    ranking: K,
+    template: G,
+    dimension: usize,
+
+    // Strategy parameters
+    lambda: usize,
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    chi_n: f64,
+
+    // Dynamic state
+    mean: Vec<f64>,
+    sigma: f64,
+    covariance: Vec<Vec<f64>>,
+    eigenvectors: Vec<Vec<f64>>,
+    eigenvalues: Vec<f64>,
+    p_sigma: Vec<f64>,
+    p_c: Vec<f64>,
+    population: Vec<G>,
+    fitness: Vec<f64>,
+    // Steps and individuals of the asked offspring, and the points to evaluate
+    pending: Vec<(Vec<f64>, G)>,
+    candidates: Vec<G>,
+    // Best individual so far with its fitness and constraint violation
+    best: Option<(G, f64, f64)>,
+    bounds: Option<Bounds>,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
+    eigen_evaluations: usize,
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>> CmaEs<G, O> {
+    pub fn new(initial_mean: G, initial_sigma: f64, objective: O) -> Self {
+        let dimension = initial_mean.dimension();
+        assert!(dimension > 0, "CMA-ES needs at least one dimension");
+        assert!(
+            initial_sigma > 0.0,
+            "The initial step size must be positive"
+        );
+
+        let lambda = 4 + (3.0 * (dimension as f64).ln()).floor() as usize;
+        let mean = initial_mean.genes().to_vec();
+        let mut strategy = CmaEs {
+            objective,
+            ranking: Unconstrained,
+            template: initial_mean,
+            dimension,
+            lambda: 0,
+            weights: Vec::new(),
+            mu_eff: 0.0,
+            c_sigma: 0.0,
+            d_sigma: 0.0,
+            c_c: 0.0,
+            c_1: 0.0,
+            c_mu: 0.0,
+            chi_n: 0.0,
+            mean,
+            sigma: initial_sigma,
+            covariance: identity(dimension),
+            eigenvectors: identity(dimension),
+            eigenvalues: vec![1.0; dimension],
+            p_sigma: vec![0.0; dimension],
+            p_c: vec![0.0; dimension],
+            population: Vec::new(),
+            fitness: Vec::new(),
+            pending: Vec::new(),
+            candidates: Vec::new(),
+            best: None,
+            bounds: None,
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+            eigen_evaluations: 0,
+        };
+        strategy.set_population_size(lambda);
+        strategy
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> CmaEs<G, O, K> {
+    // Ranks the offspring under constraints, see `crate::constraints`. The best
+    // individual so far prefers a smaller violation over a better fitness.
+    pub fn with_constraints<K2: Ranking<G>>(self, ranking: K2) -> CmaEs<G, O, K2> {
+        CmaEs {
+            objective: self.objective,
+            ranking,
+            template: self.template,
+            dimension: self.dimension,
+            lambda: self.lambda,
+            weights: self.weights,
+            mu_eff: self.mu_eff,
+            c_sigma: self.c_sigma,
+            d_sigma: self.d_sigma,
+            c_c: self.c_c,
+            c_1: self.c_1,
+            c_mu: self.c_mu,
+            chi_n: self.chi_n,
+            mean: self.mean,
+            sigma: self.sigma,
+            covariance: self.covariance,
+            eigenvectors: self.eigenvectors,
+            eigenvalues: self.eigenvalues,
+            p_sigma: self.p_sigma,
+            p_c: self.p_c,
+            population: self.population,
+            fitness: self.fitness,
+            pending: self.pending,
+            candidates: self.candidates,
+            best: None,
+            bounds: self.bounds,
+            rng: self.rng,
+            generation: self.generation,
+            evaluations: self.evaluations,
+            eigen_evaluations: self.eigen_evaluations,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    // Every sampled offspring is repaired (or penalized) according to the bounds
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        assert_eq!(
+            bounds.dimension(),
+            self.dimension,
+            "The bounds do not match the dimension of the search space"
+        );
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_population_size(mut self, lambda: usize) -> Self {
+        self.set_population_size(lambda);
+        self
+    }
+
+    // Sets lambda and derives all other strategy parameters from the defaults
+    fn set_population_size(&mut self, lambda: usize) {
+        assert!(lambda >= 2, "CMA-ES needs a population size of at least 2");
+        let n = self.dimension as f64;
+        let mu = lambda / 2;
+
+        let raw_weights: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let weight_sum: f64 = raw_weights.iter().sum();
+        let weights: Vec<f64> = raw_weights.iter().map(|w| w / weight_sum).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_1 = 2.0 / ((n + 1.3).powi(2) + mu_eff);
+        let c_mu =
+            (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0).powi(2) + mu_eff));
+
+        self.lambda = lambda;
+        self.weights = weights;
+        self.mu_eff = mu_eff;
+        self.c_sigma = c_sigma;
+        self.d_sigma = d_sigma;
+        self.c_c = c_c;
+        self.c_1 = c_1;
+        self.c_mu = c_mu;
+        self.chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.objective.evaluate_batch(&self.candidates);
+        for (individual, fitness) in self.candidates.iter().zip(&fitness) {
+            on_evaluation(individual, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    // The offspring of the next generation. With bounds these are the points
+    // at which the objective is evaluated, see `Bounds::evaluation_point`.
+    pub fn ask(&mut self) -> &[G] {
+        if !self.candidates.is_empty() {
+            return &self.candidates;
+        }
+
+        // Sample y_k = B D z_k and x_k = m + sigma y_k
+        let n = self.dimension;
+        for _ in 0..self.lambda {
+            let z: Vec<f64> = (0..n)
+                .map(|_| StandardNormal.sample(&mut self.rng))
+                .collect();
+            let scaled: Vec<f64> = z
+                .iter()
+                .zip(&self.eigenvalues)
+                .map(|(z, d)| z * d.sqrt())
+                .collect();
+            let y = mat_vec(&self.eigenvectors, &scaled);
+
+            let mut individual = self.template.clone();
+            for ((gene, m), y) in individual.genes_mut().iter_mut().zip(&self.mean).zip(&y) {
+                *gene = m + self.sigma * y;
+            }
+
+            match &self.bounds {
+                Some(bounds) => {
+                    // Learn from the repaired point, which keeps the mean inside the box
+                    bounds.repair(&mut individual, &mut self.rng);
+                    let y = individual
+                        .genes()
+                        .iter()
+                        .zip(&self.mean)
+                        .map(|(x, m)| (x - m) / self.sigma)
+                        .collect();
+                    self.candidates.push(bounds.evaluation_point(&individual));
+                    self.pending.push((y, individual));
+                }
+                None => {
+                    self.candidates.push(individual.clone());
+                    self.pending.push((y, individual));
+                }
+            }
+        }
+        &self.candidates
+    }
+
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.candidates, fitness)?;
+        let n = self.dimension;
+        let direction = self.objective.direction();
+        self.candidates.clear();
+        let samples: Vec<(Vec<f64>, G, f64)> = self
+            .pending
+            .drain(..)
+            .zip(fitness)
+            .map(|((y, individual), fitness)| {
+                let fitness = match &self.bounds {
+                    Some(bounds) => bounds.penalize(*fitness, &individual, direction),
+                    None => *fitness,
+                };
+                (y, individual, fitness)
+            })
+            .collect();
+        self.evaluations += self.lambda;
+
+        let individuals: Vec<G> = samples.iter().map(|(_, x, _)| x.clone()).collect();
+        let fitness: Vec<f64> = samples.iter().map(|(_, _, f)| *f).collect();
+        let violations = self.ranking.violations(&individuals);
+        let order = self
+            .ranking
+            .rank(&fitness, &violations, direction, &mut self.rng);
+        let best_violation = violations[order[0]];
+        let samples: Vec<(Vec<f64>, G, f64)> =
+            order.iter().map(|index| samples[*index].clone()).collect();
+
+        let (_, best_offspring, best_fitness) = &samples[0];
+        if self.best.as_ref().is_none_or(|(_, f, v)| {
+            best_violation < *v || (best_violation == *v && direction.is_better(*best_fitness, *f))
+        }) {
+            self.best = Some((best_offspring.clone(), *best_fitness, best_violation));
+        }
+
+        // Weighted recombination of the mu best steps
+        let mut y_w = vec![0.0; n];
+        for ((y, _, _), w) in samples.iter().zip(&self.weights) {
+            for (acc, y) in y_w.iter_mut().zip(y) {
+                *acc += w * y;
+            }
+        }
+        for (m, y) in self.mean.iter_mut().zip(&y_w) {
+            *m += self.sigma * y;
+        }
+
+        // Cumulative step-size adaptation, using C^(-1/2) y_w = B D^(-1/2) B^T y_w
+        let projected = mat_t_vec(&self.eigenvectors, &y_w);
+        let whitened: Vec<f64> = projected
+            .iter()
+            .zip(&self.eigenvalues)
+            .map(|(v, d)| v / d.sqrt())
+            .collect();
+        let c_inv_sqrt_y = mat_vec(&self.eigenvectors, &whitened);
+        let cs_factor = (self.c_sigma * (2.0 - self.c_sigma) * self.mu_eff).sqrt();
+        for (p, v) in self.p_sigma.iter_mut().zip(&c_inv_sqrt_y) {
+            *p = (1.0 - self.c_sigma) * *p + cs_factor * v;
+        }
+        let p_sigma_norm = norm(&self.p_sigma);
+
+        // Stall the rank-one update if p_sigma is large
+        let generations = (self.generation + 1) as f64;
+        let h_sigma = p_sigma_norm / (1.0 - (1.0 - self.c_sigma).powf(2.0 * generations)).sqrt()
+            < (1.4 + 2.0 / (n as f64 + 1.0)) * self.chi_n;
+        let h_sigma = if h_sigma { 1.0 } else { 0.0 };
+
+        let cc_factor = (self.c_c * (2.0 - self.c_c) * self.mu_eff).sqrt();
+        for (p, y) in self.p_c.iter_mut().zip(&y_w) {
+            *p = (1.0 - self.c_c) * *p + h_sigma * cc_factor * y;
+        }
+
+        // Rank-one and rank-mu covariance matrix update
+        let delta_h = (1.0 - h_sigma) * self.c_c * (2.0 - self.c_c);
+        let decay = 1.0 - self.c_1 - self.c_mu;
+        for i in 0..n {
+            for j in 0..=i {
+                let rank_one = self.p_c[i] * self.p_c[j] + delta_h * self.covariance[i][j];
+                let rank_mu: f64 = samples
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|((y, _, _), w)| w * y[i] * y[j])
+                    .sum();
+                let value =
+                    decay * self.covariance[i][j] + self.c_1 * rank_one + self.c_mu * rank_mu;
+                self.covariance[i][j] = value;
+                self.covariance[j][i] = value;
+            }
+        }
+
+        self.sigma *= ((self.c_sigma / self.d_sigma) * (p_sigma_norm / self.chi_n - 1.0)).exp();
+
+        // Update B and D lazily, the decomposition is O(n^3)
+        let gap = self.lambda as f64 / ((self.c_1 + self.c_mu) * n as f64 * 10.0);
+        if (self.evaluations - self.eigen_evaluations) as f64 > gap {
+            self.update_eigendecomposition();
+        }
+
+        (self.population, self.fitness) = samples
+            .into_iter()
+            .map(|(_, individual, fitness)| (individual, fitness))
+            .unzip();
+        self.generation += 1;
+        Ok(())
+    }
+
+    fn update_eigendecomposition(&mut self) {
+        let (eigenvalues, eigenvectors) = symmetric_eigen(&self.covariance);
+        // Guard against numerical noise producing tiny negative eigenvalues
+        self.eigenvalues = eigenvalues.into_iter().map(|d| d.max(1e-20)).collect();
+        self.eigenvectors = eigenvectors;
+        self.eigen_evaluations = self.evaluations;
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    pub fn covariance(&self) -> &[Vec<f64>] {
+        &self.covariance
+    }
+
+    pub fn population_size(&self) -> usize {
+        self.lambda
+    }
+
+    // The offspring of the last generation, best first
+    pub fn population(&self) -> &[G] {
+        &self.population
+    }
+
+    // The best individual found so far, or the initial mean before the first generation
+    pub fn best_individual(&self) -> &G {
+        self.best
+            .as_ref()
+            .map_or(&self.template, |(individual, _, _)| individual)
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best.as_ref().map(|(_, fitness, _)| *fitness)
+    }
+
+    pub fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+fn mat_vec(matrix: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    matrix
+        .iter()
+        .map(|row| row.iter().zip(v).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+fn mat_t_vec(matrix: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; v.len()];
+    for (row, x) in matrix.iter().zip(v) {
+        for (acc, a) in result.iter_mut().zip(row) {
+            *acc += a * x;
+        }
+    }
+    result
+}
+
+// Cyclic Jacobi eigenvalue algorithm for symmetric matrices. Returns the
+// eigenvalues and a matrix whose columns are the corresponding eigenvectors.
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut v = identity(n);
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal < 1e-30 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let akp = row[p];
+                    let akq = row[q];
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (apk, aqk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (old_p, old_q) = (*apk, *aqk);
+                    *apk = c * old_p - s * old_q;
+                    *aqk = s * old_p + c * old_q;
+                }
+                for row in v.iter_mut() {
+                    let vkp = row[p];
+                    let vkq = row[q];
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> Optimizer<G> for CmaEs<G, O, K> {
+    fn step(&mut self) {
+        CmaEs::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        CmaEs::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        CmaEs::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        CmaEs::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        CmaEs::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        CmaEs::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        CmaEs::population(self)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        &self.fitness
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        CmaEs::direction(self)
+    }
+
+    fn step_size(&self) -> Option<f64> {
+        Some(self.sigma())
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> AskTell<G> for CmaEs<G, O, K> {
+    fn ask(&mut self) -> &[G] {
+        CmaEs::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        CmaEs::tell(self, fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Rosenbrock, Sphere};
+    use crate::objective::Minimize;
+
+    #[test]
+    fn test_symmetric_eigen_reconstructs_matrix() {
+        let matrix = vec![
+            vec![4.0, 1.0, 0.5],
+            vec![1.0, 3.0, 0.2],
+            vec![0.5, 0.2, 1.0],
+        ];
+        let (values, vectors) = symmetric_eigen(&matrix);
+
+        for i in 0..3 {
+            for j in 0..3 {
+                let reconstructed: f64 = (0..3)
+                    .map(|k| vectors[i][k] * values[k] * vectors[j][k])
+                    .sum();
+                assert!((reconstructed - matrix[i][j]).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cma_es_rosenbrock() {
+        let mut strategy = CmaEs::new(vec![0.0; 5], 0.5, Rosenbrock).with_seed(3);
+        strategy.run(1000);
+
+        assert!(
+            strategy.best_fitness().unwrap() < 1e-8,
+            "Fitness too high: {:?}",
+            strategy.best_fitness()
+        );
+        for value in strategy.mean() {
+            assert!((value - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_cma_es_learns_ellipsoid_scaling() {
+        // The second coordinate is 100 times more sensitive than the first
+        let ellipsoid = |x: &Vec<f64>| -> f64 { x[0].powi(2) + 1e4 * x[1].powi(2) };
+
+        let mut strategy = CmaEs::new(vec![1.0, 1.0], 1.0, Minimize(ellipsoid)).with_seed(1);
+        strategy.run(150);
+
+        let covariance = strategy.covariance();
+        assert!(covariance[0][0] > 100.0 * covariance[1][1]);
+    }
+
+    #[test]
+    fn test_cma_es_seed_reproducibility() {
+        let run = |seed: u64| {
+            let mut strategy = CmaEs::new(vec![1.0; 4], 0.3, Sphere).with_seed(seed);
+            strategy.run(50);
+            (strategy.mean().to_vec(), strategy.sigma())
+        };
+
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+    }
+
+    #[test]
+    fn test_cma_es_respects_bounds() {
+        // The unconstrained optimum at (2, 2, 2) lies outside the box
+        let objective = |x: &Vec<f64>| -> f64 {
+            assert!(x.iter().all(|v| (-1.0..=1.0).contains(v)));
+            x.iter().map(|v| (v - 2.0).powi(2)).sum()
+        };
+        let bounds = Bounds::uniform(3, -1.0, 1.0);
+
+        let mut strategy = CmaEs::new(vec![0.0; 3], 0.5, Minimize(objective))
+            .with_bounds(bounds)
+            .with_seed(5);
+        strategy.run(200);
+
+        for value in strategy.best_individual() {
+            assert!((value - 1.0).abs() < 1e-6);
+        }
+    }
+}
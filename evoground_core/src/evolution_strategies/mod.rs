@@ -2,10 +2,44 @@
 //
 // This module contains the implementation of the evolution strategies algorithm.
 
-pub trait Mutate {
-    fn mutate(&self, individual: &mut f64);
+mod adaptive;
+mod cma_es;
+mod mu_lambda;
+mod mutation;
+mod recombination;
+mod restart;
+mod selection;
+mod self_adaptive;
+
+pub use adaptive::{AdaptiveMutate, AdaptiveOnePlusOneStrategy};
+pub use cma_es::CmaEs;
+pub use mu_lambda::{MuCommaLambdaStrategy, MuPlusLambdaStrategy};
+pub use mutation::{CauchyMutator, GaussianMutator, LevyMutator};
+pub use recombination::{
+    DiscreteRecombination, IntermediateRecombination, NoRecombination, Recombine,
+};
+pub use restart::{
+    RestartFactory, RestartParameters, RestartPolicy, RestartStrategy, StagnationCriteria,
+};
+pub use selection::{
+    BoltzmannSelector, ExponentialRankSelector, LinearRankSelector, RouletteSelector,
+    StochasticUniversalSampling, TournamentSelector,
+};
+pub use self_adaptive::{SelfAdaptive, SelfAdaptiveMutator};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::constraints::{Ranking, Unconstrained};
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+use crate::optimizer::{check_tell, AskTell, Optimizer, TellError};
+
+pub trait Mutate<G> {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R);
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleMutator {
     mutation_rate: f64,
     mutation_size: f64,
@@ -20,123 +54,392 @@ impl SimpleMutator {
     }
 }
 
-impl Mutate for SimpleMutator {
-    fn mutate(&self, individual: &mut f64) {
-        if rand::random::<f64>() < self.mutation_rate {
-            *individual += (rand::random::<f64>() * 2.0 - 1.0) * self.mutation_size;
+impl<G: Genome<Gene = f64>> Mutate<G> for SimpleMutator {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        // Each gene is perturbed independently with probability `mutation_rate`
+        for gene in individual.genes_mut() {
+            if rng.gen::<f64>() < self.mutation_rate {
+                *gene += (rng.gen::<f64>() * 2.0 - 1.0) * self.mutation_size;
+            }
+        }
+    }
+}
+
+impl<G: Genome<Gene = f64>> AdaptiveMutate<G> for SimpleMutator {
+    fn step_size(&self) -> f64 {
+        self.mutation_size
+    }
+
+    fn set_step_size(&mut self, step_size: f64) {
+        self.mutation_size = step_size;
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitFlipMutator {
+    mutation_rate: f64,
+}
+
+impl BitFlipMutator {
+    pub fn new(mutation_rate: f64) -> BitFlipMutator {
+        BitFlipMutator { mutation_rate }
+    }
+}
+
+impl<G: Genome<Gene = bool>> Mutate<G> for BitFlipMutator {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        for gene in individual.genes_mut() {
+            if rng.gen::<f64>() < self.mutation_rate {
+                *gene = !*gene;
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegerMutator {
+    mutation_rate: f64,
+    max_step: i64,
+}
+
+impl IntegerMutator {
+    pub fn new(mutation_rate: f64, max_step: i64) -> IntegerMutator {
+        IntegerMutator {
+            mutation_rate,
+            max_step,
         }
     }
 }
 
-pub trait Select {
-    fn select(&self, population: &Vec<f64>) -> Vec<f64>;
+impl<G: Genome<Gene = i64>> Mutate<G> for IntegerMutator {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        // Steps are drawn uniformly from [-max_step, max_step], excluding zero
+        for gene in individual.genes_mut() {
+            if self.max_step > 0 && rng.gen::<f64>() < self.mutation_rate {
+                let step = rng.gen_range(1..=self.max_step);
+                if rng.gen::<bool>() {
+                    *gene += step;
+                } else {
+                    *gene -= step;
+                }
+            }
+        }
+    }
+}
+
+pub trait Select<G> {
+    // The fitness of every individual according to the selector's objective
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64>;
+
+    fn direction(&self) -> OptimizationDirection;
+
+    // Indices into `population` of the selected individuals, given the fitness
+    // of every individual. Stochastic selectors may return the same index more
+    // than once.
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize>;
+
+    fn select_indices<R: Rng + ?Sized>(&mut self, population: &[G], rng: &mut R) -> Vec<usize> {
+        let fitness = self.evaluate(population);
+        self.select_evaluated(population, &fitness, rng)
+    }
+
+    fn select<R: Rng + ?Sized>(&mut self, population: &[G], rng: &mut R) -> Vec<G>
+    where
+        G: Clone,
+    {
+        self.select_indices(population, rng)
+            .into_iter()
+            .map(|index| population[index].clone())
+            .collect()
+    }
 }
 
-pub struct SimpleSelector {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimpleSelector<O, K = Unconstrained> {
     selection_size: usize,
-    objective: fn(&f64) -> f64,
+    objective: O,
+    ranking: K,
 }
 
-impl SimpleSelector {
-    pub fn new(selection_size: usize, objective: fn(&f64) -> f64) -> SimpleSelector {
+impl<O> SimpleSelector<O> {
+    pub fn new(selection_size: usize, objective: O) -> SimpleSelector<O> {
         SimpleSelector {
             selection_size,
             objective,
+            ranking: Unconstrained,
         }
     }
 }
 
-impl Select for SimpleSelector {
-    fn select(&self, population: &Vec<f64>) -> Vec<f64> {
-        let mut population = population.clone();
-
-        // Sort the population based on the objective function's output
-        population.sort_by(|a, b| {
-            let score_a = (self.objective)(a);
-            let score_b = (self.objective)(b);
+impl<O, K> SimpleSelector<O, K> {
+    // Ranks the population under constraints, see `crate::constraints`
+    pub fn with_constraints<K2>(self, ranking: K2) -> SimpleSelector<O, K2> {
+        SimpleSelector {
+            selection_size: self.selection_size,
+            objective: self.objective,
+            ranking,
+        }
+    }
+}
 
-            // For descending order (higher scores first), swap the order of comparison
-            score_b
-                .partial_cmp(&score_a)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+impl<G, O: Objective<G>, K: Ranking<G>> Select<G> for SimpleSelector<O, K> {
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64> {
+        self.objective.evaluate_batch(population)
+    }
 
-        // Select the top `selection_size` elements
-        population.truncate(self.selection_size);
+    fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
 
-        population
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize> {
+        // Order the population best first and select the top `selection_size` elements
+        let violations = self.ranking.violations(population);
+        let order = self
+            .ranking
+            .rank(fitness, &violations, self.objective.direction(), rng);
+        order.into_iter().take(self.selection_size).collect()
     }
 }
 
-pub struct OnePlusOneStrategy {
-    individual: f64,
-    mutator: SimpleMutator,
-    objective: fn(f64) -> f64,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnePlusOneStrategy<G, M, O, K = Unconstrained> {
+    individual: G,
+    // Fitness and constraint violation of `individual`, known after the first
+    // generation
+    fitness: Option<f64>,
+    violation: f64,
+    pending: Vec<G>,
+    mutator: M,
+    objective: O,
+    ranking: K,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
 }
 
-impl OnePlusOneStrategy {
-    pub fn new(initial_value: f64, mutator: SimpleMutator, objective: fn(f64) -> f64) -> Self {
+impl<G: Genome, M: Mutate<G>, O: Objective<G>> OnePlusOneStrategy<G, M, O> {
+    pub fn new(initial_value: G, mutator: M, objective: O) -> Self {
         OnePlusOneStrategy {
             individual: initial_value,
+            fitness: None,
+            violation: 0.0,
+            pending: Vec::new(),
             mutator,
             objective,
+            ranking: Unconstrained,
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, O: Objective<G>, K: Ranking<G>> OnePlusOneStrategy<G, M, O, K> {
+    // Compares parent and offspring under constraints, see `crate::constraints`
+    pub fn with_constraints<K2: Ranking<G>>(self, ranking: K2) -> OnePlusOneStrategy<G, M, O, K2> {
+        OnePlusOneStrategy {
+            individual: self.individual,
+            fitness: self.fitness,
+            violation: self.violation,
+            pending: self.pending,
+            mutator: self.mutator,
+            objective: self.objective,
+            ranking,
+            rng: self.rng,
+            generation: self.generation,
+            evaluations: self.evaluations,
+        }
+    }
+
+    // Identical seeds give identical runs
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.objective.evaluate_batch(&self.pending);
+        for (individual, fitness) in self.pending.iter().zip(&fitness) {
+            on_evaluation(individual, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    // The offspring of the next generation, preceded by the parent in the
+    // first generation
+    pub fn ask(&mut self) -> &[G] {
+        if self.pending.is_empty() {
+            if self.fitness.is_none() {
+                self.pending.push(self.individual.clone());
+            }
+            let mut offspring = self.individual.clone();
+            self.mutator.mutate(&mut offspring, &mut self.rng);
+            self.pending.push(offspring);
         }
+        &self.pending
+    }
+
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.pending, fitness)?;
+        // Only the parent of the first generation is told along with the offspring
+        let violations = self.ranking.violations(&self.pending);
+        let offspring_fitness = fitness[fitness.len() - 1];
+        let offspring_violation = violations[violations.len() - 1];
+        let (parent_fitness, parent_violation) = match self.fitness {
+            Some(fitness) => (fitness, self.violation),
+            None => (fitness[0], violations[0]),
+        };
+        let offspring = self.pending.pop().unwrap();
+        self.pending.clear();
+
+        // The offspring replaces the parent only if it ranks strictly better
+        let direction = self.objective.direction();
+        let order = self.ranking.rank(
+            &[parent_fitness, offspring_fitness],
+            &[parent_violation, offspring_violation],
+            direction,
+            &mut self.rng,
+        );
+        if order[0] == 1 {
+            self.individual = offspring;
+            self.fitness = Some(offspring_fitness);
+            self.violation = offspring_violation;
+        } else {
+            self.fitness = Some(parent_fitness);
+            self.violation = parent_violation;
+        }
+        self.evaluations += fitness.len();
+        self.generation += 1;
+        Ok(())
     }
 
     pub fn run(&mut self, generations: usize) {
         for _ in 0..generations {
-            let mut offspring = self.individual;
-            self.mutator.mutate(&mut offspring);
-            if (self.objective)(offspring) > (self.objective)(self.individual) {
-                self.individual = offspring;
-            }
+            self.step();
         }
     }
 
-    pub fn best_individual(&self) -> f64 {
-        self.individual
+    pub fn best_individual(&self) -> &G {
+        &self.individual
+    }
+
+    // Fitness of the current individual, known once the first generation has
+    // evaluated the starting point
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.fitness
+    }
+
+    pub fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, O: Objective<G>, K: Ranking<G>> Optimizer<G>
+    for OnePlusOneStrategy<G, M, O, K>
+{
+    fn step(&mut self) {
+        OnePlusOneStrategy::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        OnePlusOneStrategy::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        OnePlusOneStrategy::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        OnePlusOneStrategy::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        OnePlusOneStrategy::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        OnePlusOneStrategy::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        std::slice::from_ref(&self.individual)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        self.fitness.as_slice()
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        OnePlusOneStrategy::direction(self)
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, O: Objective<G>, K: Ranking<G>> AskTell<G>
+    for OnePlusOneStrategy<G, M, O, K>
+{
+    fn ask(&mut self) -> &[G] {
+        OnePlusOneStrategy::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        OnePlusOneStrategy::tell(self, fitness)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::Rng; // Import necessary items from the outer module
+    use crate::genome::BitString;
+    use crate::objective::Minimize;
 
     #[test]
     fn test_one_plus_one_es_multiple_runs() {
         let runs = 100; // Number of runs
         let mut total_distance = 0.0;
-        let mut worst_distance = 0.0;
 
-        for _ in 0..runs {
+        for run in 0..runs {
             let mutator = SimpleMutator::new(0.1, 0.5); // Example mutation parameters
-            let objective = |x: f64| -> f64 { -(x - 2.0).powi(2) + 10.0 }; // Example objective function
-            let mut rand = rand::thread_rng();
+            let objective = |x: &f64| -> f64 { -(x - 2.0).powi(2) + 10.0 }; // Example objective function
+            let mut rand = ChaCha8Rng::seed_from_u64(run);
             let initial_value = rand.gen_range(0.0..5.0); // Initialize the individual with a random value
 
-            let mut strategy = OnePlusOneStrategy::new(initial_value, mutator, objective);
+            let mut strategy =
+                OnePlusOneStrategy::new(initial_value, mutator, objective).with_seed(run);
 
             // Run the evolutionary strategy for 1000 generations
             strategy.run(1000);
 
             let distance = (strategy.best_individual() - 2.0).abs();
             total_distance += distance;
-
-            // Update worst distance if the current run's distance is greater
-            if distance > worst_distance {
-                worst_distance = distance;
-            }
         }
 
         // Calculate the average distance from the optimal value
         let average_distance = total_distance / runs as f64;
 
-        println!(
-            "Average distance from optimal value: {}, Worst distance: {}",
-            average_distance, worst_distance
-        );
-
         // Assert on the average distance to check the effectiveness of the strategy
         assert!(
             average_distance < 0.1,
@@ -144,4 +447,130 @@ mod tests {
             average_distance
         );
     }
+
+    #[test]
+    fn test_one_plus_one_es_real_vector() {
+        let mutator = SimpleMutator::new(0.3, 0.5);
+        let objective = |x: &Vec<f64>| -> f64 { -x.iter().map(|v| (v - 1.0).powi(2)).sum::<f64>() };
+
+        let mut strategy = OnePlusOneStrategy::new(vec![0.0; 5], mutator, objective).with_seed(1);
+        strategy.run(5000);
+
+        for value in strategy.best_individual() {
+            assert!(
+                (value - 1.0).abs() < 0.1,
+                "Gene did not converge: {}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_one_plus_one_es_fixed_size_array() {
+        let mutator = SimpleMutator::new(0.5, 0.5);
+        let objective = |x: &[f64; 2]| -> f64 { -(x[0] + 1.0).powi(2) - (x[1] - 3.0).powi(2) };
+
+        let mut strategy = OnePlusOneStrategy::new([0.0, 0.0], mutator, objective).with_seed(2);
+        strategy.run(3000);
+
+        let best = strategy.best_individual();
+        assert!((best[0] + 1.0).abs() < 0.1 && (best[1] - 3.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_one_plus_one_es_bitstring() {
+        let mutator = BitFlipMutator::new(1.0 / 16.0);
+        let objective = |x: &BitString| -> f64 { x.iter().filter(|bit| **bit).count() as f64 };
+
+        let mut strategy =
+            OnePlusOneStrategy::new(vec![false; 16], mutator, objective).with_seed(3);
+        strategy.run(2000);
+
+        assert!(strategy.best_individual().iter().all(|bit| *bit));
+    }
+
+    #[test]
+    fn test_one_plus_one_es_capturing_objective() {
+        // The target is captured by the closure, so it cannot coerce to a function pointer
+        let target = vec![0.5, -1.5, 2.0];
+        let objective = |x: &Vec<f64>| -> f64 {
+            -x.iter()
+                .zip(&target)
+                .map(|(v, t)| (v - t).powi(2))
+                .sum::<f64>()
+        };
+
+        let mut strategy =
+            OnePlusOneStrategy::new(vec![0.0; 3], SimpleMutator::new(0.5, 0.5), objective)
+                .with_seed(4);
+        strategy.run(3000);
+
+        for (value, t) in strategy.best_individual().iter().zip(&target) {
+            assert!((value - t).abs() < 0.1, "Gene did not converge: {}", value);
+        }
+    }
+
+    struct CountingObjective {
+        evaluations: usize,
+    }
+
+    impl Objective<f64> for CountingObjective {
+        fn evaluate(&mut self, individual: &f64) -> f64 {
+            self.evaluations += 1;
+            -individual.powi(2)
+        }
+    }
+
+    #[test]
+    fn test_simple_selector_stateful_objective() {
+        let mut selector = SimpleSelector::new(2, CountingObjective { evaluations: 0 });
+        let selected = selector.select(&[3.0, -0.5, 1.0, 0.25], &mut ChaCha8Rng::seed_from_u64(1));
+
+        assert_eq!(selected, vec![0.25, -0.5]);
+        assert!(selector.objective.evaluations > 0);
+    }
+
+    #[test]
+    fn test_one_plus_one_es_minimization() {
+        let objective =
+            Minimize(|x: &Vec<f64>| -> f64 { x.iter().map(|v| (v - 2.0).powi(2)).sum() });
+
+        let mut strategy =
+            OnePlusOneStrategy::new(vec![0.0; 3], SimpleMutator::new(0.5, 0.5), objective)
+                .with_seed(5);
+        // Nothing is evaluated before the first generation
+        assert_eq!(strategy.best_fitness(), None);
+        assert_eq!(strategy.evaluations(), 0);
+        strategy.run(3000);
+
+        assert_eq!(strategy.direction(), OptimizationDirection::Minimize);
+        assert!(
+            strategy.best_fitness().unwrap() < 0.01,
+            "Fitness too high: {:?}",
+            strategy.best_fitness()
+        );
+    }
+
+    #[test]
+    fn test_simple_selector_minimization() {
+        let mut selector = SimpleSelector::new(2, Minimize(|x: &f64| x.abs()));
+        let selected = selector.select(&[3.0, -0.5, 1.0, 0.25], &mut ChaCha8Rng::seed_from_u64(2));
+
+        assert_eq!(selected, vec![0.25, -0.5]);
+    }
+
+    #[test]
+    fn test_one_plus_one_es_seed_reproducibility() {
+        let objective = |x: &Vec<f64>| -> f64 { -x.iter().map(|v| v.powi(2)).sum::<f64>() };
+        let run = |seed: u64| {
+            let mut strategy =
+                OnePlusOneStrategy::new(vec![1.0; 4], SimpleMutator::new(0.5, 0.5), objective)
+                    .with_seed(seed);
+            strategy.run(200);
+            strategy.best_individual().clone()
+        };
+
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
 }
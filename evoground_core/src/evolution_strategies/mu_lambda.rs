@@ -0,0 +1,546 @@
+// Population based evolution strategies
+//
+// Both strategies create `lambda` offspring per generation by mutating
+// uniformly chosen parents. The comma variant selects the next `mu` parents
+// from the offspring only, while the plus variant lets parents compete with
+// their offspring. `mu` is the number of individuals returned by the selector.
+// Without a recombinator every offspring inherits from a single parent.
+//
+// With ask/tell the comma variant asks for the offspring of every generation.
+// The plus variant also asks for the initial parents in the first generation
+// and afterwards remembers the fitness of the surviving parents.
+//
+// Stochastic selectors return their parents in random order and may drop the
+// best individual, so both strategies keep track of the best one evaluated.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use super::{Mutate, NoRecombination, Recombine, Select};
+use crate::evaluation::Evaluated;
+use crate::genome::Genome;
+use crate::objective::OptimizationDirection;
+use crate::optimizer::{check_tell, AskTell, Optimizer, TellError};
+
+fn create_offspring<G, M: Mutate<G>, C: Recombine<G>, R: Rng + ?Sized>(
+    parents: &[G],
+    lambda: usize,
+    mutator: &M,
+    recombinator: &C,
+    rng: &mut R,
+) -> Vec<G> {
+    (0..lambda)
+        .map(|_| {
+            let mut offspring = recombinator.recombine(parents, rng);
+            mutator.mutate(&mut offspring, rng);
+            offspring
+        })
+        .collect()
+}
+
+fn update_best<G: Clone>(
+    best: &mut Option<Evaluated<G>>,
+    candidates: &[G],
+    fitness: &[f64],
+    direction: OptimizationDirection,
+) {
+    for (individual, fitness) in candidates.iter().zip(fitness) {
+        if best
+            .as_ref()
+            .is_none_or(|best| direction.is_better(*fitness, best.fitness))
+        {
+            *best = Some(Evaluated::new(individual.clone(), *fitness));
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MuCommaLambdaStrategy<G, M, S, R = NoRecombination> {
+    population: Vec<G>,
+    lambda: usize,
+    mutator: M,
+    selector: S,
+    recombinator: R,
+    // Fitness of the current population, empty before the first generation
+    fitness: Vec<f64>,
+    pending: Vec<G>,
+    best: Option<Evaluated<G>>,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>> MuCommaLambdaStrategy<G, M, S> {
+    pub fn new(initial_population: Vec<G>, lambda: usize, mutator: M, selector: S) -> Self {
+        assert!(
+            !initial_population.is_empty(),
+            "The initial population must not be empty"
+        );
+        assert!(
+            lambda > 0,
+            "At least one offspring per generation is required"
+        );
+
+        MuCommaLambdaStrategy {
+            population: initial_population,
+            lambda,
+            mutator,
+            selector,
+            recombinator: NoRecombination,
+            fitness: Vec::new(),
+            pending: Vec::new(),
+            best: None,
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>, R: Recombine<G>> MuCommaLambdaStrategy<G, M, S, R> {
+    pub fn with_recombination<R2: Recombine<G>>(
+        self,
+        recombinator: R2,
+    ) -> MuCommaLambdaStrategy<G, M, S, R2> {
+        MuCommaLambdaStrategy {
+            population: self.population,
+            lambda: self.lambda,
+            mutator: self.mutator,
+            selector: self.selector,
+            recombinator,
+            fitness: self.fitness,
+            pending: self.pending,
+            best: self.best,
+            rng: self.rng,
+            generation: self.generation,
+            evaluations: self.evaluations,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.selector.evaluate(&self.pending);
+        for (individual, fitness) in self.pending.iter().zip(&fitness) {
+            on_evaluation(individual, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    pub fn ask(&mut self) -> &[G] {
+        if self.pending.is_empty() {
+            self.pending = create_offspring(
+                &self.population,
+                self.lambda,
+                &self.mutator,
+                &self.recombinator,
+                &mut self.rng,
+            );
+        }
+        &self.pending
+    }
+
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.pending, fitness)?;
+        let direction = self.selector.direction();
+        update_best(&mut self.best, &self.pending, fitness, direction);
+        let selected = self
+            .selector
+            .select_evaluated(&self.pending, fitness, &mut self.rng);
+        self.population = selected
+            .iter()
+            .map(|index| self.pending[*index].clone())
+            .collect();
+        self.fitness = selected.iter().map(|index| fitness[*index]).collect();
+        self.pending.clear();
+        self.evaluations += fitness.len();
+        self.generation += 1;
+        Ok(())
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    pub fn population(&self) -> &[G] {
+        &self.population
+    }
+
+    // The best individual found so far, or the first one of the initial population
+    pub fn best_individual(&self) -> &G {
+        self.best
+            .as_ref()
+            .map_or(&self.population[0], |best| &best.genome)
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best.as_ref().map(|best| best.fitness)
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Number of offspring handed to the selector, which evaluates each of them
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MuPlusLambdaStrategy<G, M, S, R = NoRecombination> {
+    population: Vec<G>,
+    lambda: usize,
+    mutator: M,
+    selector: S,
+    recombinator: R,
+    // Fitness of the current population, empty before the first generation
+    fitness: Vec<f64>,
+    pending: Vec<G>,
+    best: Option<Evaluated<G>>,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>> MuPlusLambdaStrategy<G, M, S> {
+    pub fn new(initial_population: Vec<G>, lambda: usize, mutator: M, selector: S) -> Self {
+        assert!(
+            !initial_population.is_empty(),
+            "The initial population must not be empty"
+        );
+        assert!(
+            lambda > 0,
+            "At least one offspring per generation is required"
+        );
+
+        MuPlusLambdaStrategy {
+            population: initial_population,
+            lambda,
+            mutator,
+            selector,
+            recombinator: NoRecombination,
+            fitness: Vec::new(),
+            pending: Vec::new(),
+            best: None,
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>, R: Recombine<G>> MuPlusLambdaStrategy<G, M, S, R> {
+    pub fn with_recombination<R2: Recombine<G>>(
+        self,
+        recombinator: R2,
+    ) -> MuPlusLambdaStrategy<G, M, S, R2> {
+        MuPlusLambdaStrategy {
+            population: self.population,
+            lambda: self.lambda,
+            mutator: self.mutator,
+            selector: self.selector,
+            recombinator,
+            fitness: self.fitness,
+            pending: self.pending,
+            best: self.best,
+            rng: self.rng,
+            generation: self.generation,
+            evaluations: self.evaluations,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.selector.evaluate(&self.pending);
+        for (individual, fitness) in self.pending.iter().zip(&fitness) {
+            on_evaluation(individual, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    // The offspring, followed by the initial parents in the first generation
+    pub fn ask(&mut self) -> &[G] {
+        if self.pending.is_empty() {
+            self.pending = create_offspring(
+                &self.population,
+                self.lambda,
+                &self.mutator,
+                &self.recombinator,
+                &mut self.rng,
+            );
+            if self.fitness.is_empty() {
+                self.pending.extend(self.population.iter().cloned());
+            }
+        }
+        &self.pending
+    }
+
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.pending, fitness)?;
+        self.evaluations += fitness.len();
+        let direction = self.selector.direction();
+        update_best(&mut self.best, &self.pending, fitness, direction);
+
+        // Parents compete with their offspring using their remembered fitness
+        let mut candidates: Vec<G> = self.pending.drain(..).collect();
+        let mut candidate_fitness = fitness.to_vec();
+        if !self.fitness.is_empty() {
+            candidates.append(&mut self.population);
+            candidate_fitness.append(&mut self.fitness);
+        }
+
+        let selected =
+            self.selector
+                .select_evaluated(&candidates, &candidate_fitness, &mut self.rng);
+        self.population = selected
+            .iter()
+            .map(|index| candidates[*index].clone())
+            .collect();
+        self.fitness = selected
+            .iter()
+            .map(|index| candidate_fitness[*index])
+            .collect();
+        self.generation += 1;
+        Ok(())
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    pub fn population(&self) -> &[G] {
+        &self.population
+    }
+
+    // The best individual found so far, or the first one of the initial population
+    pub fn best_individual(&self) -> &G {
+        self.best
+            .as_ref()
+            .map_or(&self.population[0], |best| &best.genome)
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best.as_ref().map(|best| best.fitness)
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Number of evaluated candidates. Parents are only evaluated once.
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>, R: Recombine<G>> Optimizer<G>
+    for MuCommaLambdaStrategy<G, M, S, R>
+{
+    fn step(&mut self) {
+        MuCommaLambdaStrategy::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        MuCommaLambdaStrategy::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        MuCommaLambdaStrategy::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        MuCommaLambdaStrategy::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        MuCommaLambdaStrategy::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        MuCommaLambdaStrategy::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        MuCommaLambdaStrategy::population(self)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        &self.fitness
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.selector.direction()
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>, R: Recombine<G>> Optimizer<G>
+    for MuPlusLambdaStrategy<G, M, S, R>
+{
+    fn step(&mut self) {
+        MuPlusLambdaStrategy::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        MuPlusLambdaStrategy::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        MuPlusLambdaStrategy::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        MuPlusLambdaStrategy::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        MuPlusLambdaStrategy::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        MuPlusLambdaStrategy::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        MuPlusLambdaStrategy::population(self)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        &self.fitness
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.selector.direction()
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>, R: Recombine<G>> AskTell<G>
+    for MuCommaLambdaStrategy<G, M, S, R>
+{
+    fn ask(&mut self) -> &[G] {
+        MuCommaLambdaStrategy::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        MuCommaLambdaStrategy::tell(self, fitness)
+    }
+}
+
+impl<G: Genome, M: Mutate<G>, S: Select<G>, R: Recombine<G>> AskTell<G>
+    for MuPlusLambdaStrategy<G, M, S, R>
+{
+    fn ask(&mut self) -> &[G] {
+        MuPlusLambdaStrategy::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        MuPlusLambdaStrategy::tell(self, fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere, Transformed};
+    use crate::evolution_strategies::{SimpleMutator, SimpleSelector, TournamentSelector};
+
+    // Optimum at (1, 1, 1, 1), away from the origin
+    fn shifted_sphere() -> Transformed<Sphere> {
+        Transformed::new(Sphere).with_shift(vec![1.0; 4])
+    }
+
+    #[test]
+    fn test_mu_comma_lambda_sphere() {
+        let initial_population = vec![vec![5.0; 4]; 5];
+        let sphere = shifted_sphere();
+        let selector = SimpleSelector::new(5, sphere.clone());
+        let mut strategy = MuCommaLambdaStrategy::new(
+            initial_population,
+            30,
+            SimpleMutator::new(0.5, 0.1),
+            selector,
+        )
+        .with_seed(1);
+
+        strategy.run(300);
+
+        assert_eq!(strategy.generation(), 300);
+        assert_eq!(strategy.population().len(), 5);
+        assert!(sphere.value(strategy.best_individual()) < 0.05);
+    }
+
+    #[test]
+    fn test_mu_plus_lambda_sphere() {
+        let initial_population = vec![vec![5.0; 4]; 5];
+        let sphere = shifted_sphere();
+        let selector = SimpleSelector::new(5, sphere.clone());
+        let mut strategy = MuPlusLambdaStrategy::new(
+            initial_population,
+            30,
+            SimpleMutator::new(0.5, 0.1),
+            selector,
+        )
+        .with_seed(2);
+
+        let mut previous = sphere.value(strategy.best_individual());
+        for _ in 0..300 {
+            strategy.step();
+            // Elitism: the best individual can never get worse
+            let current = sphere.value(strategy.best_individual());
+            assert!(current <= previous);
+            previous = current;
+        }
+
+        assert!(previous < 0.01);
+    }
+
+    #[test]
+    fn test_best_is_kept_with_stochastic_selection() {
+        let initial_population = vec![vec![5.0; 4]; 5];
+        let sphere = shifted_sphere();
+        let selector = TournamentSelector::new(5, 2, sphere.clone());
+        let mut strategy = MuPlusLambdaStrategy::new(
+            initial_population,
+            10,
+            SimpleMutator::new(0.5, 0.1),
+            selector,
+        )
+        .with_seed(4);
+
+        let mut previous = f64::INFINITY;
+        for _ in 0..100 {
+            strategy.step();
+            let current = strategy.best_fitness().unwrap();
+            assert!(current <= previous);
+            assert_eq!(current, sphere.value(strategy.best_individual()));
+            assert!(strategy
+                .population()
+                .iter()
+                .all(|x| sphere.value(x) >= current));
+            previous = current;
+        }
+    }
+}
@@ -0,0 +1,225 @@
+// Mutation operators for real-valued genomes
+//
+// Every gene is perturbed independently with probability `mutation_rate`. The
+// operators differ only in the distribution of the perturbation: Gaussian
+// steps are the classic ES choice, while Cauchy and Levy steps are heavy
+// tailed and occasionally make long jumps that help to escape local optima.
+
+use std::f64::consts::PI;
+
+use rand::Rng;
+use rand_distr::{Cauchy, Distribution, StandardNormal};
+
+use super::{AdaptiveMutate, Mutate};
+use crate::genome::Genome;
+
+fn mutate_genes<G, R, F>(individual: &mut G, mutation_rate: f64, rng: &mut R, mut step: F)
+where
+    G: Genome<Gene = f64>,
+    R: Rng + ?Sized,
+    F: FnMut(&mut R) -> f64,
+{
+    for gene in individual.genes_mut() {
+        if rng.gen::<f64>() < mutation_rate {
+            *gene += step(rng);
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GaussianMutator {
+    mutation_rate: f64,
+    sigma: f64,
+}
+
+impl GaussianMutator {
+    pub fn new(mutation_rate: f64, sigma: f64) -> GaussianMutator {
+        GaussianMutator {
+            mutation_rate,
+            sigma,
+        }
+    }
+}
+
+impl<G: Genome<Gene = f64>> Mutate<G> for GaussianMutator {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        mutate_genes(individual, self.mutation_rate, rng, |rng| {
+            let z: f64 = StandardNormal.sample(rng);
+            self.sigma * z
+        });
+    }
+}
+
+impl<G: Genome<Gene = f64>> AdaptiveMutate<G> for GaussianMutator {
+    fn step_size(&self) -> f64 {
+        self.sigma
+    }
+
+    fn set_step_size(&mut self, step_size: f64) {
+        self.sigma = step_size;
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CauchyMutator {
+    mutation_rate: f64,
+    scale: f64,
+}
+
+impl CauchyMutator {
+    pub fn new(mutation_rate: f64, scale: f64) -> CauchyMutator {
+        CauchyMutator {
+            mutation_rate,
+            scale,
+        }
+    }
+}
+
+impl<G: Genome<Gene = f64>> Mutate<G> for CauchyMutator {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        let cauchy = Cauchy::new(0.0, 1.0).unwrap();
+        mutate_genes(individual, self.mutation_rate, rng, |rng| {
+            self.scale * cauchy.sample(rng)
+        });
+    }
+}
+
+impl<G: Genome<Gene = f64>> AdaptiveMutate<G> for CauchyMutator {
+    fn step_size(&self) -> f64 {
+        self.scale
+    }
+
+    fn set_step_size(&mut self, step_size: f64) {
+        self.scale = step_size;
+    }
+}
+
+// Levy flight steps generated with Mantegna's algorithm. The stability index
+// `alpha` lies in (0, 2], smaller values give heavier tails.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LevyMutator {
+    mutation_rate: f64,
+    scale: f64,
+    alpha: f64,
+    sigma_u: f64,
+}
+
+impl LevyMutator {
+    pub fn new(mutation_rate: f64, scale: f64) -> LevyMutator {
+        LevyMutator::with_alpha(mutation_rate, scale, 1.5)
+    }
+
+    pub fn with_alpha(mutation_rate: f64, scale: f64, alpha: f64) -> LevyMutator {
+        assert!(
+            alpha > 0.0 && alpha <= 2.0,
+            "The stability index must be in (0, 2]"
+        );
+        let numerator = gamma(1.0 + alpha) * (PI * alpha / 2.0).sin();
+        let denominator = gamma((1.0 + alpha) / 2.0) * alpha * 2f64.powf((alpha - 1.0) / 2.0);
+        LevyMutator {
+            mutation_rate,
+            scale,
+            alpha,
+            sigma_u: (numerator / denominator).powf(1.0 / alpha),
+        }
+    }
+}
+
+impl<G: Genome<Gene = f64>> Mutate<G> for LevyMutator {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut G, rng: &mut R) {
+        mutate_genes(individual, self.mutation_rate, rng, |rng| {
+            let u: f64 = StandardNormal.sample(rng);
+            let v: f64 = StandardNormal.sample(rng);
+            self.scale * self.sigma_u * u / v.abs().powf(1.0 / self.alpha)
+        });
+    }
+}
+
+impl<G: Genome<Gene = f64>> AdaptiveMutate<G> for LevyMutator {
+    fn step_size(&self) -> f64 {
+        self.scale
+    }
+
+    fn set_step_size(&mut self, step_size: f64) {
+        self.scale = step_size;
+    }
+}
+
+// Lanczos approximation of the gamma function (g = 7, n = 9)
+fn gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        PI / ((PI * x).sin() * gamma(1.0 - x))
+    } else {
+        let x = x - 1.0;
+        let t = x + 7.5;
+        let series = COEFFICIENTS[1..]
+            .iter()
+            .enumerate()
+            .fold(COEFFICIENTS[0], |acc, (i, c)| {
+                acc + c / (x + i as f64 + 1.0)
+            });
+        (2.0 * PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * series
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::Sphere;
+    use crate::evolution_strategies::AdaptiveOnePlusOneStrategy;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_gamma_known_values() {
+        assert!((gamma(1.0) - 1.0).abs() < 1e-12);
+        assert!((gamma(5.0) - 24.0).abs() < 1e-9);
+        assert!((gamma(0.5) - PI.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mutators_converge_on_sphere() {
+        let start = vec![3.0; 3];
+
+        let mut gaussian =
+            AdaptiveOnePlusOneStrategy::new(start.clone(), GaussianMutator::new(1.0, 1.0), Sphere)
+                .with_seed(1);
+        let mut cauchy =
+            AdaptiveOnePlusOneStrategy::new(start.clone(), CauchyMutator::new(1.0, 1.0), Sphere)
+                .with_seed(2);
+        let mut levy =
+            AdaptiveOnePlusOneStrategy::new(start, LevyMutator::new(1.0, 1.0), Sphere).with_seed(3);
+
+        gaussian.run(2000);
+        cauchy.run(2000);
+        levy.run(2000);
+
+        assert!(gaussian.best_fitness().unwrap() < 1e-6);
+        assert!(cauchy.best_fitness().unwrap() < 1e-6);
+        assert!(levy.best_fitness().unwrap() < 1e-6);
+    }
+
+    #[test]
+    fn test_zero_mutation_rate_keeps_genome() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let mut individual = [1.0, 2.0, 3.0];
+        GaussianMutator::new(0.0, 1.0).mutate(&mut individual, &mut rng);
+        CauchyMutator::new(0.0, 1.0).mutate(&mut individual, &mut rng);
+        LevyMutator::new(0.0, 1.0).mutate(&mut individual, &mut rng);
+
+        assert_eq!(individual, [1.0, 2.0, 3.0]);
+    }
+}
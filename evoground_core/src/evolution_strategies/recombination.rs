@@ -0,0 +1,131 @@
+// Recombination operators
+//
+// A recombinator creates the base individual for an offspring from the parent
+// population. Strategy parameters carried by the genome are recombined
+// together with the object parameters.
+
+use rand::seq::index::sample;
+use rand::Rng;
+
+use crate::genome::Genome;
+
+pub trait Recombine<G> {
+    fn recombine<R: Rng + ?Sized>(&self, parents: &[G], rng: &mut R) -> G;
+}
+
+// Inherits everything from a single, uniformly chosen parent
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoRecombination;
+
+impl<G: Genome> Recombine<G> for NoRecombination {
+    fn recombine<R: Rng + ?Sized>(&self, parents: &[G], rng: &mut R) -> G {
+        parents[rng.gen_range(0..parents.len())].clone()
+    }
+}
+
+// Chooses `rho` distinct parents uniformly, clamped to the population size
+fn choose_parents<'a, G, R: Rng + ?Sized>(parents: &'a [G], rho: usize, rng: &mut R) -> Vec<&'a G> {
+    let rho = rho.clamp(1, parents.len());
+    sample(rng, parents.len(), rho)
+        .into_iter()
+        .map(|i| &parents[i])
+        .collect()
+}
+
+fn average_into(target: &mut [f64], sources: &[&[f64]]) {
+    for (i, value) in target.iter_mut().enumerate() {
+        *value = sources.iter().map(|source| source[i]).sum::<f64>() / sources.len() as f64;
+    }
+}
+
+// Intermediate (mu/rho_I) recombination: the offspring is the centroid of
+// `rho` parents, for object and strategy parameters alike
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntermediateRecombination {
+    rho: usize,
+}
+
+impl IntermediateRecombination {
+    pub fn new(rho: usize) -> IntermediateRecombination {
+        IntermediateRecombination { rho }
+    }
+}
+
+impl<G: Genome<Gene = f64>> Recombine<G> for IntermediateRecombination {
+    fn recombine<R: Rng + ?Sized>(&self, parents: &[G], rng: &mut R) -> G {
+        let chosen = choose_parents(parents, self.rho, rng);
+        let mut offspring = chosen[0].clone();
+
+        let genes: Vec<&[f64]> = chosen.iter().map(|parent| parent.genes()).collect();
+        average_into(offspring.genes_mut(), &genes);
+
+        let parameters: Vec<&[f64]> = chosen
+            .iter()
+            .map(|parent| parent.strategy_parameters())
+            .collect();
+        average_into(offspring.strategy_parameters_mut(), &parameters);
+
+        offspring
+    }
+}
+
+// Discrete (mu/rho_D) recombination: every object parameter is copied from a
+// randomly chosen one of `rho` parents. Strategy parameters are recombined
+// intermediately, as recommended for self-adaptation.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiscreteRecombination {
+    rho: usize,
+}
+
+impl DiscreteRecombination {
+    pub fn new(rho: usize) -> DiscreteRecombination {
+        DiscreteRecombination { rho }
+    }
+}
+
+impl<G: Genome> Recombine<G> for DiscreteRecombination
+where
+    G::Gene: Clone,
+{
+    fn recombine<R: Rng + ?Sized>(&self, parents: &[G], rng: &mut R) -> G {
+        let chosen = choose_parents(parents, self.rho, rng);
+        let mut offspring = chosen[0].clone();
+
+        for (i, gene) in offspring.genes_mut().iter_mut().enumerate() {
+            *gene = chosen[rng.gen_range(0..chosen.len())].genes()[i].clone();
+        }
+
+        let parameters: Vec<&[f64]> = chosen
+            .iter()
+            .map(|parent| parent.strategy_parameters())
+            .collect();
+        average_into(offspring.strategy_parameters_mut(), &parameters);
+
+        offspring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_intermediate_recombination_centroid() {
+        let parents = vec![vec![0.0, 2.0], vec![2.0, 4.0]];
+        let offspring = IntermediateRecombination::new(2)
+            .recombine(&parents, &mut ChaCha8Rng::seed_from_u64(1));
+
+        assert_eq!(offspring, vec![1.0, 3.0]);
+    }
+
+    #[test]
+    fn test_discrete_recombination_copies_parent_genes() {
+        let parents = vec![vec![0, 0, 0], vec![1, 1, 1]];
+        let offspring =
+            DiscreteRecombination::new(2).recombine(&parents, &mut ChaCha8Rng::seed_from_u64(2));
+
+        assert!(offspring.iter().all(|gene| *gene == 0 || *gene == 1));
+    }
+}
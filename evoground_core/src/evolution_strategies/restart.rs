@@ -0,0 +1,505 @@
+// Restart wrappers (IPOP and BIPOP)
+//
+// The wrapper runs an inner strategy until it stagnates and then replaces it
+// with a fresh one built by a user supplied factory. With IPOP every restart
+// increases the population size by a constant factor. BIPOP interleaves those
+// large-population restarts with cheap restarts using a small, randomly sized
+// population and a reduced initial step size, always running the regime that
+// has used fewer evaluations so far. The best individual is tracked across
+// all restarts.
+//
+// The first inner strategy is built by the first step, ask or `start`, so it
+// is seeded from the wrapper's seed. Until then there is no population, and
+// `direction`, `current` and the best individual are not available.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::evaluation::Evaluated;
+use crate::objective::OptimizationDirection;
+use crate::optimizer::{AskTell, Optimizer, TellError};
+
+// Builds the inner strategy of every restart. Any closure taking the restart
+// parameters is a factory, a named type can be used to serialize the wrapper.
+// The inner strategy can be any optimizer. Strategies without a global step
+// size (see `Optimizer::step_size`) only restart on stagnating fitness.
+pub trait RestartFactory<S> {
+    fn build(&mut self, parameters: RestartParameters) -> S;
+}
+
+impl<S, F: FnMut(RestartParameters) -> S> RestartFactory<S> for F {
+    fn build(&mut self, parameters: RestartParameters) -> S {
+        self(parameters)
+    }
+}
+
+// Everything the factory needs to know to build the next inner strategy
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RestartParameters {
+    pub restart: usize,
+    pub population_size: usize,
+    // Multiplier for the initial step size, below 1 for small BIPOP regimes
+    pub step_size_factor: f64,
+    // Seed for the inner strategy, drawn from the wrapper's generator
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StagnationCriteria {
+    // Improvements of the best fitness smaller than this are not counted
+    pub tol_fun: f64,
+    // Restart once the step size drops below this value
+    pub tol_x: f64,
+    // Restart after this many generations without improvement
+    pub max_stagnation: usize,
+}
+
+impl Default for StagnationCriteria {
+    fn default() -> Self {
+        StagnationCriteria {
+            tol_fun: 1e-12,
+            tol_x: 1e-12,
+            max_stagnation: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RestartPolicy {
+    Ipop { increase_factor: f64 },
+    Bipop { increase_factor: f64 },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RestartStrategy<G, S, F> {
+    factory: F,
+    inner: Option<S>,
+    policy: RestartPolicy,
+    criteria: StagnationCriteria,
+    default_population_size: usize,
+    restarts: usize,
+    large_restarts: usize,
+    large_evaluations: usize,
+    small_evaluations: usize,
+    in_small_regime: bool,
+    finished_evaluations: usize,
+    inner_best: Option<f64>,
+    stagnant_generations: usize,
+    best: Option<Evaluated<G>>,
+    rng: ChaCha8Rng,
+    generation: usize,
+}
+
+impl<G: Clone, S: Optimizer<G>, F: RestartFactory<S>> RestartStrategy<G, S, F> {
+    pub fn ipop(default_population_size: usize, factory: F) -> Self {
+        Self::new(
+            RestartPolicy::Ipop {
+                increase_factor: 2.0,
+            },
+            default_population_size,
+            factory,
+        )
+    }
+
+    pub fn bipop(default_population_size: usize, factory: F) -> Self {
+        Self::new(
+            RestartPolicy::Bipop {
+                increase_factor: 2.0,
+            },
+            default_population_size,
+            factory,
+        )
+    }
+
+    pub fn new(policy: RestartPolicy, default_population_size: usize, factory: F) -> Self {
+        assert!(
+            default_population_size > 0,
+            "The population size must be positive"
+        );
+
+        RestartStrategy {
+            factory,
+            inner: None,
+            policy,
+            criteria: StagnationCriteria::default(),
+            default_population_size,
+            restarts: 0,
+            large_restarts: 0,
+            large_evaluations: 0,
+            small_evaluations: 0,
+            in_small_regime: false,
+            finished_evaluations: 0,
+            inner_best: None,
+            stagnant_generations: 0,
+            best: None,
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+        }
+    }
+
+    // The seeds of all inner strategies are drawn from this seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        assert!(self.inner.is_none(), "Seed the strategy before running it");
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn with_stagnation_criteria(mut self, criteria: StagnationCriteria) -> Self {
+        self.criteria = criteria;
+        self
+    }
+
+    // Builds the first inner strategy unless that has already happened
+    pub fn start(&mut self) {
+        if self.inner.is_none() {
+            let parameters = RestartParameters {
+                restart: 0,
+                population_size: self.default_population_size,
+                step_size_factor: 1.0,
+                seed: self.rng.gen(),
+            };
+            self.build(parameters);
+        }
+    }
+
+    fn build(&mut self, parameters: RestartParameters) {
+        let mut inner = self.factory.build(parameters);
+        inner.start();
+        self.inner = Some(inner);
+    }
+
+    fn inner(&self) -> &S {
+        self.inner
+            .as_ref()
+            .expect("The inner strategy is built by the first step")
+    }
+
+    fn inner_mut(&mut self) -> &mut S {
+        self.start();
+        self.inner.as_mut().unwrap()
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.inner_mut().step_observed(on_evaluation);
+        self.finish_generation();
+    }
+
+    // Records the result of a completed inner generation and restarts if the
+    // inner strategy has stagnated
+    fn finish_generation(&mut self) {
+        self.generation += 1;
+
+        let inner = self.inner();
+        let direction = inner.direction();
+        let fitness = inner.best_fitness().unwrap_or(direction.worst());
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|best| direction.is_better(fitness, best.fitness))
+        {
+            self.best = Some(Evaluated::new(inner.best_individual().clone(), fitness));
+        }
+
+        // An improvement only counts if it exceeds the fitness tolerance
+        let improved = match self.inner_best {
+            None => true,
+            Some(previous) => {
+                direction.is_better(fitness, previous)
+                    && (fitness - previous).abs() > self.criteria.tol_fun
+            }
+        };
+        if improved {
+            self.inner_best = Some(fitness);
+            self.stagnant_generations = 0;
+        } else {
+            self.stagnant_generations += 1;
+        }
+
+        let step_size_collapsed = self
+            .inner()
+            .step_size()
+            .is_some_and(|sigma| sigma < self.criteria.tol_x);
+        if step_size_collapsed || self.stagnant_generations >= self.criteria.max_stagnation {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        let evaluations = self.inner().evaluations();
+        self.finished_evaluations += evaluations;
+        if self.in_small_regime {
+            self.small_evaluations += evaluations;
+        } else {
+            self.large_evaluations += evaluations;
+        }
+        self.restarts += 1;
+
+        let parameters = match self.policy {
+            RestartPolicy::Ipop { increase_factor } => {
+                self.large_restarts += 1;
+                RestartParameters {
+                    restart: self.restarts,
+                    population_size: self.large_population_size(increase_factor),
+                    step_size_factor: 1.0,
+                    seed: self.rng.gen(),
+                }
+            }
+            RestartPolicy::Bipop { increase_factor } => {
+                // The first restart always goes to the large regime
+                self.in_small_regime =
+                    self.large_restarts > 0 && self.small_evaluations < self.large_evaluations;
+                if self.in_small_regime {
+                    let u: f64 = self.rng.gen();
+                    let large = self.large_population_size(increase_factor) as f64;
+                    let ratio = large / self.default_population_size as f64 / 2.0;
+                    let population_size =
+                        (self.default_population_size as f64 * ratio.powf(u * u)).floor() as usize;
+                    RestartParameters {
+                        restart: self.restarts,
+                        population_size: population_size.max(self.default_population_size),
+                        step_size_factor: 10f64.powf(-2.0 * self.rng.gen::<f64>()),
+                        seed: self.rng.gen(),
+                    }
+                } else {
+                    self.large_restarts += 1;
+                    RestartParameters {
+                        restart: self.restarts,
+                        population_size: self.large_population_size(increase_factor),
+                        step_size_factor: 1.0,
+                        seed: self.rng.gen(),
+                    }
+                }
+            }
+        };
+
+        self.build(parameters);
+        self.inner_best = None;
+        self.stagnant_generations = 0;
+    }
+
+    fn large_population_size(&self, increase_factor: f64) -> usize {
+        (self.default_population_size as f64 * increase_factor.powi(self.large_restarts as i32))
+            .round() as usize
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    // The best individual over all restarts, known after the first generation
+    pub fn overall_best(&self) -> Option<&G> {
+        self.best.as_ref().map(|best| &best.genome)
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best.as_ref().map(|best| best.fitness)
+    }
+
+    // The currently running inner strategy
+    pub fn current(&self) -> &S {
+        self.inner()
+    }
+
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.finished_evaluations + self.inner.as_ref().map_or(0, |inner| inner.evaluations())
+    }
+}
+
+impl<G, S: Optimizer<G>, F: RestartFactory<S>> Optimizer<G> for RestartStrategy<G, S, F>
+where
+    G: Clone,
+{
+    fn step(&mut self) {
+        RestartStrategy::step(self)
+    }
+
+    fn start(&mut self) {
+        RestartStrategy::start(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        RestartStrategy::step_observed(self, on_evaluation)
+    }
+
+    // Falls back to the running inner strategy before the first generation
+    fn best_individual(&self) -> &G {
+        match self.overall_best() {
+            Some(best) => best,
+            None => self.inner().best_individual(),
+        }
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        RestartStrategy::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        RestartStrategy::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        RestartStrategy::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        self.inner.as_ref().map_or(&[], |inner| inner.population())
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        self.inner
+            .as_ref()
+            .map_or(&[], |inner| inner.population_fitness())
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.inner().direction()
+    }
+
+    fn step_size(&self) -> Option<f64> {
+        self.inner.as_ref().and_then(|inner| inner.step_size())
+    }
+}
+
+// Asks the running inner strategy. A restart happens after the tell that
+// completes a stagnant generation, so the next ask goes to the new strategy.
+impl<G, S, F> AskTell<G> for RestartStrategy<G, S, F>
+where
+    G: Clone,
+    S: Optimizer<G> + AskTell<G>,
+    F: RestartFactory<S>,
+{
+    fn ask(&mut self) -> &[G] {
+        self.inner_mut().ask()
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        let inner = self.inner_mut();
+        let generation = inner.generation();
+        inner.tell(fitness)?;
+        // Telling the initial population of some strategies is not a generation
+        if inner.generation() > generation {
+            self.finish_generation();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::Rastrigin;
+    use crate::differential_evolution::DifferentialEvolution;
+    use crate::evolution_strategies::CmaEs;
+    use crate::termination::MaxGenerations;
+    use crate::testing::random_population;
+
+    fn cma_factory(parameters: RestartParameters) -> CmaEs<Vec<f64>, Rastrigin> {
+        let mut rng = ChaCha8Rng::seed_from_u64(parameters.seed);
+        let start: Vec<f64> = (0..3).map(|_| rng.gen_range(-5.0..5.0)).collect();
+        CmaEs::new(start, 2.0 * parameters.step_size_factor, Rastrigin)
+            .with_population_size(parameters.population_size)
+            .with_seed(parameters.seed)
+    }
+
+    #[test]
+    fn test_ipop_cma_es_rastrigin() {
+        let mut strategy = RestartStrategy::ipop(7, cma_factory).with_seed(1);
+        while strategy.best_fitness().is_none_or(|f| f > 1e-8) && strategy.restarts() < 8 {
+            strategy.step();
+        }
+
+        assert!(
+            strategy.best_fitness().unwrap() < 1e-8,
+            "Fitness too high: {:?}",
+            strategy.best_fitness()
+        );
+        assert_eq!(
+            strategy.current().population_size(),
+            7 * 2usize.pow(strategy.restarts() as u32)
+        );
+    }
+
+    #[test]
+    fn test_bipop_tracks_best_across_restarts() {
+        let mut strategy = RestartStrategy::bipop(7, cma_factory).with_seed(2);
+
+        let mut previous = f64::INFINITY;
+        while strategy.restarts() < 4 {
+            strategy.step();
+            let current = strategy.best_fitness().unwrap();
+            assert!(current <= previous);
+            previous = current;
+        }
+
+        assert!(strategy.evaluations() > strategy.current().evaluations());
+    }
+
+    #[test]
+    fn test_restart_seed_reproducibility() {
+        let run = |seed: u64| {
+            let mut strategy = RestartStrategy::bipop(7, cma_factory).with_seed(seed);
+            strategy.run(400);
+            (strategy.restarts(), strategy.overall_best().cloned())
+        };
+
+        assert_eq!(run(3), run(3));
+    }
+
+    #[test]
+    fn test_ipop_differential_evolution() {
+        let factory = |parameters: RestartParameters| {
+            let population = random_population(parameters.population_size, 3, parameters.seed);
+            DifferentialEvolution::new(population, Rastrigin).with_seed(parameters.seed)
+        };
+        let mut strategy = RestartStrategy::ipop(10, factory)
+            .with_stagnation_criteria(StagnationCriteria {
+                max_stagnation: 20,
+                ..StagnationCriteria::default()
+            })
+            .with_seed(1);
+
+        // Without a step size the inner strategy only restarts on stagnation
+        let mut previous = f64::INFINITY;
+        while strategy.restarts() < 2 {
+            strategy.step();
+            let current = strategy.best_fitness().unwrap();
+            assert!(current <= previous);
+            previous = current;
+        }
+        assert_eq!(strategy.current().population().len(), 40);
+    }
+
+    #[test]
+    fn test_factory_builds_first_strategy_once() {
+        let mut builds = 0;
+        let factory = |parameters: RestartParameters| {
+            builds += 1;
+            cma_factory(parameters)
+        };
+        let mut strategy = RestartStrategy::ipop(7, factory).with_seed(4);
+        let result = strategy.run_until(&mut MaxGenerations::new(5));
+        assert_eq!(result.generations, 5);
+        assert_eq!(strategy.restarts(), 0);
+        drop(strategy);
+
+        assert_eq!(builds, 1);
+    }
+}
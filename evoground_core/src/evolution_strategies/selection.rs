@@ -0,0 +1,654 @@
+// Stochastic selection operators
+//
+// Unlike the truncation of `SimpleSelector`, these selectors give every
+// individual a chance to reproduce and draw `selection_size` parents with
+// replacement. Fitness proportional selectors (roulette, stochastic universal
+// sampling) use the distance to the worst fitness of the population as weight,
+// so they work for both optimization directions and for negative fitness
+// values. Non-finite fitness values are never preferred.
+//
+// Every selector accepts constraints through `with_constraints`. Tournament
+// and rank based selectors compare individuals by their constrained rank.
+// Fitness proportional selectors hand out the fitness values of the
+// population again in ranking order, so the best ranked individual receives
+// the best value and the weights keep the scale of the fitness.
+
+use rand::Rng;
+
+use super::Select;
+use crate::constraints::{Ranking, Unconstrained};
+use crate::objective::{Objective, OptimizationDirection};
+
+// Population indices ordered best first
+fn ranked<G, K: Ranking<G>, R: Rng + ?Sized>(
+    ranking: &mut K,
+    population: &[G],
+    fitness: &[f64],
+    direction: OptimizationDirection,
+    rng: &mut R,
+) -> Vec<usize> {
+    let violations = ranking.violations(population);
+    ranking.rank(fitness, &violations, direction, rng)
+}
+
+// The fitness values reassigned so that they are ordered like `order`
+fn reordered(fitness: &[f64], order: &[usize], direction: OptimizationDirection) -> Vec<f64> {
+    let mut sorted = fitness.to_vec();
+    sorted.sort_by(|a, b| direction.compare(*a, *b));
+    let mut result = vec![0.0; fitness.len()];
+    for (index, value) in order.iter().zip(sorted) {
+        result[*index] = value;
+    }
+    result
+}
+
+// Distance of every fitness value to the worst one, zero for non-finite values
+fn proportional_weights(fitness: &[f64], direction: OptimizationDirection) -> Vec<f64> {
+    let finite = fitness.iter().copied().filter(|f| f.is_finite());
+    let worst = match direction {
+        OptimizationDirection::Minimize => finite.fold(f64::NEG_INFINITY, f64::max),
+        OptimizationDirection::Maximize => finite.fold(f64::INFINITY, f64::min),
+    };
+    fitness
+        .iter()
+        .map(|f| {
+            if f.is_finite() {
+                (f - worst).abs()
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+fn cumulative(weights: &[f64]) -> Vec<f64> {
+    // Without any positive weight every individual is equally likely
+    let uniform = !weights.iter().any(|w| *w > 0.0);
+    weights
+        .iter()
+        .scan(0.0, |total, w| {
+            *total += if uniform { 1.0 } else { *w };
+            Some(*total)
+        })
+        .collect()
+}
+
+// The first index whose cumulative weight exceeds `point`
+fn find(cumulative: &[f64], point: f64) -> usize {
+    cumulative
+        .partition_point(|c| *c <= point)
+        .min(cumulative.len() - 1)
+}
+
+// Draws `count` indices independently with probabilities proportional to `weights`
+fn spin_roulette<R: Rng + ?Sized>(weights: &[f64], count: usize, rng: &mut R) -> Vec<usize> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let cumulative = cumulative(weights);
+    let total = cumulative[cumulative.len() - 1];
+    (0..count)
+        .map(|_| find(&cumulative, rng.gen::<f64>() * total))
+        .collect()
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TournamentSelector<O, K = Unconstrained> {
+    selection_size: usize,
+    tournament_size: usize,
+    probability: f64,
+    objective: O,
+    ranking: K,
+}
+
+impl<O> TournamentSelector<O> {
+    // Every parent is the winner of a tournament among `tournament_size`
+    // uniformly drawn individuals
+    pub fn new(
+        selection_size: usize,
+        tournament_size: usize,
+        objective: O,
+    ) -> TournamentSelector<O> {
+        assert!(
+            tournament_size > 0,
+            "A tournament needs at least one contestant"
+        );
+        TournamentSelector {
+            selection_size,
+            tournament_size,
+            probability: 1.0,
+            objective,
+            ranking: Unconstrained,
+        }
+    }
+}
+
+impl<O, K> TournamentSelector<O, K> {
+    // Contestants are compared by their rank under constraints, see
+    // `crate::constraints`
+    pub fn with_constraints<K2>(self, ranking: K2) -> TournamentSelector<O, K2> {
+        TournamentSelector {
+            selection_size: self.selection_size,
+            tournament_size: self.tournament_size,
+            probability: self.probability,
+            objective: self.objective,
+            ranking,
+        }
+    }
+
+    // The best contestant wins with probability p, the second best with
+    // p (1 - p) and so on
+    pub fn with_probability(mut self, probability: f64) -> Self {
+        assert!(
+            probability > 0.0 && probability <= 1.0,
+            "The winning probability must be in (0, 1]"
+        );
+        self.probability = probability;
+        self
+    }
+}
+
+impl<G, O: Objective<G>, K: Ranking<G>> Select<G> for TournamentSelector<O, K> {
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64> {
+        self.objective.evaluate_batch(population)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize> {
+        if population.is_empty() {
+            return Vec::new();
+        }
+        let direction = self.objective.direction();
+        let order = ranked(&mut self.ranking, population, fitness, direction, rng);
+        let mut rank = vec![0; population.len()];
+        for (position, index) in order.into_iter().enumerate() {
+            rank[index] = position;
+        }
+
+        (0..self.selection_size)
+            .map(|_| {
+                let mut contestants: Vec<usize> = (0..self.tournament_size)
+                    .map(|_| rng.gen_range(0..population.len()))
+                    .collect();
+                contestants.sort_by_key(|contestant| rank[*contestant]);
+
+                let last = contestants.len() - 1;
+                contestants
+                    .iter()
+                    .position(|_| rng.gen::<f64>() < self.probability)
+                    .map_or(contestants[last], |winner| contestants[winner])
+            })
+            .collect()
+    }
+}
+
+// Fitness proportional selection
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RouletteSelector<O, K = Unconstrained> {
+    selection_size: usize,
+    objective: O,
+    ranking: K,
+}
+
+impl<O> RouletteSelector<O> {
+    pub fn new(selection_size: usize, objective: O) -> RouletteSelector<O> {
+        RouletteSelector {
+            selection_size,
+            objective,
+            ranking: Unconstrained,
+        }
+    }
+}
+
+impl<O, K> RouletteSelector<O, K> {
+    pub fn with_constraints<K2>(self, ranking: K2) -> RouletteSelector<O, K2> {
+        RouletteSelector {
+            selection_size: self.selection_size,
+            objective: self.objective,
+            ranking,
+        }
+    }
+}
+
+impl<G, O: Objective<G>, K: Ranking<G>> Select<G> for RouletteSelector<O, K> {
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64> {
+        self.objective.evaluate_batch(population)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let direction = self.objective.direction();
+        let order = ranked(&mut self.ranking, population, fitness, direction, rng);
+        let weights = proportional_weights(&reordered(fitness, &order, direction), direction);
+        spin_roulette(&weights, self.selection_size, rng)
+    }
+}
+
+// Fitness proportional selection with a single spin and `selection_size`
+// equally spaced pointers, which has minimal spread around the expected
+// number of copies
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StochasticUniversalSampling<O, K = Unconstrained> {
+    selection_size: usize,
+    objective: O,
+    ranking: K,
+}
+
+impl<O> StochasticUniversalSampling<O> {
+    pub fn new(selection_size: usize, objective: O) -> StochasticUniversalSampling<O> {
+        StochasticUniversalSampling {
+            selection_size,
+            objective,
+            ranking: Unconstrained,
+        }
+    }
+}
+
+impl<O, K> StochasticUniversalSampling<O, K> {
+    pub fn with_constraints<K2>(self, ranking: K2) -> StochasticUniversalSampling<O, K2> {
+        StochasticUniversalSampling {
+            selection_size: self.selection_size,
+            objective: self.objective,
+            ranking,
+        }
+    }
+}
+
+impl<G, O: Objective<G>, K: Ranking<G>> Select<G> for StochasticUniversalSampling<O, K> {
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64> {
+        self.objective.evaluate_batch(population)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize> {
+        if population.is_empty() || self.selection_size == 0 {
+            return Vec::new();
+        }
+        let direction = self.objective.direction();
+        let order = ranked(&mut self.ranking, population, fitness, direction, rng);
+        let weights = proportional_weights(&reordered(fitness, &order, direction), direction);
+        let cumulative = cumulative(&weights);
+
+        let spacing = cumulative[cumulative.len() - 1] / self.selection_size as f64;
+        let start = rng.gen::<f64>() * spacing;
+        (0..self.selection_size)
+            .map(|i| find(&cumulative, start + i as f64 * spacing))
+            .collect()
+    }
+}
+
+// Linear ranking: the best individual is expected to be selected `pressure`
+// times, the worst 2 - `pressure` times, with `pressure` in [1, 2]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRankSelector<O, K = Unconstrained> {
+    selection_size: usize,
+    pressure: f64,
+    objective: O,
+    ranking: K,
+}
+
+impl<O> LinearRankSelector<O> {
+    pub fn new(selection_size: usize, pressure: f64, objective: O) -> LinearRankSelector<O> {
+        assert!(
+            (1.0..=2.0).contains(&pressure),
+            "The selection pressure must be in [1, 2]"
+        );
+        LinearRankSelector {
+            selection_size,
+            pressure,
+            objective,
+            ranking: Unconstrained,
+        }
+    }
+}
+
+impl<O, K> LinearRankSelector<O, K> {
+    pub fn with_constraints<K2>(self, ranking: K2) -> LinearRankSelector<O, K2> {
+        LinearRankSelector {
+            selection_size: self.selection_size,
+            pressure: self.pressure,
+            objective: self.objective,
+            ranking,
+        }
+    }
+}
+
+impl<G, O: Objective<G>, K: Ranking<G>> Select<G> for LinearRankSelector<O, K> {
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64> {
+        self.objective.evaluate_batch(population)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let direction = self.objective.direction();
+        let order = ranked(&mut self.ranking, population, fitness, direction, rng);
+
+        let n = order.len();
+        let weights: Vec<f64> = (0..n)
+            .map(|rank| {
+                if n == 1 {
+                    1.0
+                } else {
+                    let position = (n - 1 - rank) as f64 / (n - 1) as f64;
+                    2.0 - self.pressure + 2.0 * (self.pressure - 1.0) * position
+                }
+            })
+            .collect();
+        spin_roulette(&weights, self.selection_size, rng)
+            .into_iter()
+            .map(|rank| order[rank])
+            .collect()
+    }
+}
+
+// Exponential ranking: the individual of rank i (best is 0) is selected with
+// probability proportional to `base`^i, with `base` in (0, 1)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExponentialRankSelector<O, K = Unconstrained> {
+    selection_size: usize,
+    base: f64,
+    objective: O,
+    ranking: K,
+}
+
+impl<O> ExponentialRankSelector<O> {
+    pub fn new(selection_size: usize, base: f64, objective: O) -> ExponentialRankSelector<O> {
+        assert!(base > 0.0 && base < 1.0, "The base must be in (0, 1)");
+        ExponentialRankSelector {
+            selection_size,
+            base,
+            objective,
+            ranking: Unconstrained,
+        }
+    }
+}
+
+impl<O, K> ExponentialRankSelector<O, K> {
+    pub fn with_constraints<K2>(self, ranking: K2) -> ExponentialRankSelector<O, K2> {
+        ExponentialRankSelector {
+            selection_size: self.selection_size,
+            base: self.base,
+            objective: self.objective,
+            ranking,
+        }
+    }
+}
+
+impl<G, O: Objective<G>, K: Ranking<G>> Select<G> for ExponentialRankSelector<O, K> {
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64> {
+        self.objective.evaluate_batch(population)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let direction = self.objective.direction();
+        let order = ranked(&mut self.ranking, population, fitness, direction, rng);
+
+        let weights: Vec<f64> = (0..order.len())
+            .map(|rank| self.base.powi(rank as i32))
+            .collect();
+        spin_roulette(&weights, self.selection_size, rng)
+            .into_iter()
+            .map(|rank| order[rank])
+            .collect()
+    }
+}
+
+// Boltzmann selection: weights exp(f / T) when maximizing and exp(-f / T)
+// when minimizing. A cooling rate below one lowers the temperature after every
+// selection, which gradually increases the selection pressure.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoltzmannSelector<O, K = Unconstrained> {
+    selection_size: usize,
+    temperature: f64,
+    cooling_rate: f64,
+    objective: O,
+    ranking: K,
+}
+
+impl<O> BoltzmannSelector<O> {
+    pub fn new(selection_size: usize, temperature: f64, objective: O) -> BoltzmannSelector<O> {
+        assert!(temperature > 0.0, "The temperature must be positive");
+        BoltzmannSelector {
+            selection_size,
+            temperature,
+            cooling_rate: 1.0,
+            objective,
+            ranking: Unconstrained,
+        }
+    }
+}
+
+impl<O, K> BoltzmannSelector<O, K> {
+    pub fn with_constraints<K2>(self, ranking: K2) -> BoltzmannSelector<O, K2> {
+        BoltzmannSelector {
+            selection_size: self.selection_size,
+            temperature: self.temperature,
+            cooling_rate: self.cooling_rate,
+            objective: self.objective,
+            ranking,
+        }
+    }
+
+    pub fn with_cooling_rate(mut self, cooling_rate: f64) -> Self {
+        assert!(
+            cooling_rate > 0.0 && cooling_rate <= 1.0,
+            "The cooling rate must be in (0, 1]"
+        );
+        self.cooling_rate = cooling_rate;
+        self
+    }
+
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+}
+
+impl<G, O: Objective<G>, K: Ranking<G>> Select<G> for BoltzmannSelector<O, K> {
+    fn evaluate(&mut self, population: &[G]) -> Vec<f64> {
+        self.objective.evaluate_batch(population)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    fn select_evaluated<R: Rng + ?Sized>(
+        &mut self,
+        population: &[G],
+        fitness: &[f64],
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let direction = self.objective.direction();
+        let order = ranked(&mut self.ranking, population, fitness, direction, rng);
+        let fitness = reordered(fitness, &order, direction);
+
+        // Measured relative to the best fitness to avoid overflowing exp
+        let best =
+            fitness
+                .iter()
+                .copied()
+                .filter(|f| f.is_finite())
+                .fold(direction.worst(), |best, f| {
+                    if direction.is_better(f, best) {
+                        f
+                    } else {
+                        best
+                    }
+                });
+        let weights: Vec<f64> = fitness
+            .iter()
+            .map(|f| {
+                if f.is_finite() {
+                    (-(f - best).abs() / self.temperature).exp()
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        self.temperature *= self.cooling_rate;
+        spin_roulette(&weights, self.selection_size, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::{inequality, Constrained, FeasibilityRules};
+    use crate::objective::Minimize;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    const POPULATION: [f64; 5] = [4.0, 0.5, 3.0, -2.0, 1.0];
+
+    fn count(indices: &[usize], index: usize) -> usize {
+        indices.iter().filter(|i| **i == index).count()
+    }
+
+    #[test]
+    fn test_selectors_favor_better_individuals() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let objective = Minimize(|x: &f64| x.abs());
+
+        let results = [
+            TournamentSelector::new(1000, 2, objective).select_indices(&POPULATION, &mut rng),
+            RouletteSelector::new(1000, objective).select_indices(&POPULATION, &mut rng),
+            StochasticUniversalSampling::new(1000, objective).select_indices(&POPULATION, &mut rng),
+            LinearRankSelector::new(1000, 2.0, objective).select_indices(&POPULATION, &mut rng),
+            ExponentialRankSelector::new(1000, 0.5, objective)
+                .select_indices(&POPULATION, &mut rng),
+            BoltzmannSelector::new(1000, 1.0, objective).select_indices(&POPULATION, &mut rng),
+        ];
+
+        for indices in &results {
+            assert_eq!(indices.len(), 1000);
+            // 0.5 is the best and 4.0 the worst individual
+            assert!(count(indices, 1) > count(indices, 2));
+            assert!(count(indices, 2) >= count(indices, 0));
+        }
+    }
+
+    #[test]
+    fn test_deterministic_tournament_and_cold_boltzmann_pick_best() {
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+
+        let mut tournament = TournamentSelector::new(10, 50, |x: &f64| *x);
+        assert!(tournament
+            .select(&POPULATION, &mut rng)
+            .iter()
+            .all(|x| *x == 4.0));
+
+        let mut boltzmann = BoltzmannSelector::new(10, 1e-3, |x: &f64| *x);
+        assert!(boltzmann
+            .select(&POPULATION, &mut rng)
+            .iter()
+            .all(|x| *x == 4.0));
+    }
+
+    #[test]
+    fn test_stochastic_universal_sampling_spread() {
+        // With equal fitness every individual is selected exactly once
+        let mut selector = StochasticUniversalSampling::new(5, |_: &f64| 1.0);
+        let mut indices = selector.select_indices(&POPULATION, &mut ChaCha8Rng::seed_from_u64(3));
+        indices.sort();
+
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_linear_rank_without_pressure_is_uniform() {
+        let mut selector = LinearRankSelector::new(5000, 1.0, |x: &f64| *x);
+        let indices = selector.select_indices(&POPULATION, &mut ChaCha8Rng::seed_from_u64(4));
+
+        for index in 0..POPULATION.len() {
+            assert!((800..1200).contains(&count(&indices, index)));
+        }
+    }
+
+    #[test]
+    fn test_constrained_selectors_prefer_feasible_individuals() {
+        // Minimize |x| subject to x >= 1, so only 4, 3 and 1 are feasible and
+        // 1 is the best of them
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let objective = Minimize(|x: &f64| x.abs());
+        let constraints =
+            || Constrained::new(|x: &f64| vec![inequality(1.0 - x)], FeasibilityRules);
+
+        let results = [
+            TournamentSelector::new(1000, 2, objective)
+                .with_constraints(constraints())
+                .select_indices(&POPULATION, &mut rng),
+            RouletteSelector::new(1000, objective)
+                .with_constraints(constraints())
+                .select_indices(&POPULATION, &mut rng),
+            StochasticUniversalSampling::new(1000, objective)
+                .with_constraints(constraints())
+                .select_indices(&POPULATION, &mut rng),
+            LinearRankSelector::new(1000, 2.0, objective)
+                .with_constraints(constraints())
+                .select_indices(&POPULATION, &mut rng),
+            ExponentialRankSelector::new(1000, 0.5, objective)
+                .with_constraints(constraints())
+                .select_indices(&POPULATION, &mut rng),
+            BoltzmannSelector::new(1000, 1.0, objective)
+                .with_constraints(constraints())
+                .select_indices(&POPULATION, &mut rng),
+        ];
+
+        for indices in &results {
+            assert_eq!(indices.len(), 1000);
+            assert!(count(indices, 4) > count(indices, 1));
+            assert!(count(indices, 4) > count(indices, 3));
+        }
+
+        // A deterministic tournament over the whole population always picks
+        // the best feasible individual
+        let mut tournament =
+            TournamentSelector::new(10, 50, objective).with_constraints(constraints());
+        assert!(tournament
+            .select(&POPULATION, &mut rng)
+            .iter()
+            .all(|x| *x == 1.0));
+    }
+}
@@ -0,0 +1,209 @@
+// Self-adaptive Gaussian mutation
+//
+// Each individual carries its own mutation step sizes, either a single one
+// for all coordinates or one per coordinate. The step sizes are mutated first
+// with log-normal learning rates (tau' globally, tau per coordinate, tau_0 for
+// a single step size) and then used to perturb the object parameters, so good
+// step sizes are inherited along with the individuals they produced.
+
+use std::ops::{Deref, DerefMut};
+
+use rand::Rng;
+use rand_distr::{Distribution, StandardNormal};
+
+use super::Mutate;
+use crate::genome::Genome;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfAdaptive<G> {
+    pub genome: G,
+    pub step_sizes: Vec<f64>,
+}
+
+impl<G: Genome> SelfAdaptive<G> {
+    // One step size per coordinate
+    pub fn new(genome: G, initial_step_size: f64) -> Self {
+        let step_sizes = vec![initial_step_size; genome.dimension()];
+        SelfAdaptive { genome, step_sizes }
+    }
+
+    // A single step size shared by all coordinates
+    pub fn isotropic(genome: G, initial_step_size: f64) -> Self {
+        SelfAdaptive {
+            genome,
+            step_sizes: vec![initial_step_size],
+        }
+    }
+
+    // Either a single step size or one per coordinate
+    pub fn with_step_sizes(genome: G, step_sizes: Vec<f64>) -> Self {
+        assert_valid_step_sizes(step_sizes.len(), genome.dimension());
+        SelfAdaptive { genome, step_sizes }
+    }
+}
+
+fn assert_valid_step_sizes(count: usize, dimension: usize) {
+    assert!(
+        count == 1 || count == dimension,
+        "Expected one step size or one per coordinate, got {} for dimension {}",
+        count,
+        dimension
+    );
+}
+
+impl<G: Genome> Genome for SelfAdaptive<G> {
+    type Gene = G::Gene;
+
+    fn genes(&self) -> &[G::Gene] {
+        self.genome.genes()
+    }
+
+    fn genes_mut(&mut self) -> &mut [G::Gene] {
+        self.genome.genes_mut()
+    }
+
+    fn strategy_parameters(&self) -> &[f64] {
+        &self.step_sizes
+    }
+
+    fn strategy_parameters_mut(&mut self) -> &mut [f64] {
+        &mut self.step_sizes
+    }
+}
+
+// Lets objectives treat a self-adaptive individual like its genome
+impl<G> Deref for SelfAdaptive<G> {
+    type Target = G;
+
+    fn deref(&self) -> &G {
+        &self.genome
+    }
+}
+
+impl<G> DerefMut for SelfAdaptive<G> {
+    fn deref_mut(&mut self) -> &mut G {
+        &mut self.genome
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfAdaptiveMutator {
+    tau: f64,
+    tau_prime: f64,
+    tau_0: f64,
+    min_step_size: f64,
+}
+
+impl SelfAdaptiveMutator {
+    // Uses the standard learning rates tau = 1/sqrt(2 sqrt(n)), tau' = 1/sqrt(2n)
+    // and tau_0 = 1/sqrt(n)
+    pub fn new(dimension: usize) -> SelfAdaptiveMutator {
+        let n = dimension.max(1) as f64;
+        SelfAdaptiveMutator {
+            tau: 1.0 / (2.0 * n.sqrt()).sqrt(),
+            tau_prime: 1.0 / (2.0 * n).sqrt(),
+            tau_0: 1.0 / n.sqrt(),
+            min_step_size: 1e-12,
+        }
+    }
+
+    // tau_0 defaults to sqrt(2) tau', which is 1/sqrt(n) for the standard tau'
+    pub fn with_learning_rates(tau: f64, tau_prime: f64) -> SelfAdaptiveMutator {
+        SelfAdaptiveMutator {
+            tau,
+            tau_prime,
+            tau_0: std::f64::consts::SQRT_2 * tau_prime,
+            min_step_size: 1e-12,
+        }
+    }
+
+    // Learning rate of individuals with a single step size
+    pub fn with_isotropic_learning_rate(mut self, tau_0: f64) -> Self {
+        self.tau_0 = tau_0;
+        self
+    }
+
+    // Lower bound keeping step sizes from collapsing to zero
+    pub fn with_min_step_size(mut self, min_step_size: f64) -> Self {
+        self.min_step_size = min_step_size;
+        self
+    }
+}
+
+impl<G: Genome<Gene = f64>> Mutate<SelfAdaptive<G>> for SelfAdaptiveMutator {
+    fn mutate<R: Rng + ?Sized>(&self, individual: &mut SelfAdaptive<G>, rng: &mut R) {
+        assert_valid_step_sizes(individual.step_sizes.len(), individual.genome.dimension());
+        let mut normal = || -> f64 { StandardNormal.sample(rng) };
+
+        if individual.step_sizes.len() == 1 {
+            let sigma =
+                (individual.step_sizes[0] * (self.tau_0 * normal()).exp()).max(self.min_step_size);
+            individual.step_sizes[0] = sigma;
+            for gene in individual.genome.genes_mut() {
+                *gene += sigma * normal();
+            }
+        } else {
+            let global = self.tau_prime * normal();
+            let SelfAdaptive { genome, step_sizes } = individual;
+            for (gene, sigma) in genome.genes_mut().iter_mut().zip(step_sizes.iter_mut()) {
+                *sigma = (*sigma * (global + self.tau * normal()).exp()).max(self.min_step_size);
+                *gene += *sigma * normal();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere};
+    use crate::evolution_strategies::{
+        IntermediateRecombination, MuCommaLambdaStrategy, SimpleSelector,
+    };
+    use crate::objective::Minimize;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_self_adaptive_mu_rho_comma_lambda() {
+        let dimension = 10;
+        let initial_population = vec![SelfAdaptive::new(vec![3.0; dimension], 1.0); 15];
+        let selector =
+            SimpleSelector::new(15, Minimize(|x: &SelfAdaptive<Vec<f64>>| Sphere.value(x)));
+
+        let mut strategy = MuCommaLambdaStrategy::new(
+            initial_population,
+            100,
+            SelfAdaptiveMutator::new(dimension),
+            selector,
+        )
+        .with_recombination(IntermediateRecombination::new(15))
+        .with_seed(1);
+        strategy.run(300);
+
+        let best = strategy.best_individual();
+        assert!(
+            Sphere.value(best) < 1e-6,
+            "Fitness too high: {}",
+            Sphere.value(best)
+        );
+        // The step sizes must have been adapted to the shrinking distance to the optimum
+        assert!(best.step_sizes.iter().all(|sigma| *sigma < 1e-2));
+    }
+
+    #[test]
+    fn test_isotropic_mutation_changes_all_genes() {
+        let mut individual = SelfAdaptive::isotropic(vec![0.0; 4], 1.0);
+        SelfAdaptiveMutator::new(4).mutate(&mut individual, &mut ChaCha8Rng::seed_from_u64(1));
+
+        assert_eq!(individual.step_sizes.len(), 1);
+        assert!(individual.genome.iter().all(|gene| *gene != 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected one step size or one per coordinate")]
+    fn test_step_sizes_must_match_dimension() {
+        SelfAdaptive::with_step_sizes(vec![0.0; 4], vec![1.0; 3]);
+    }
+}
@@ -0,0 +1,265 @@
+// Crossover operators
+//
+// A crossover combines two parents into two children. The positional
+// operators (one-point, two-point and uniform) exchange genes and work for any
+// genome, while arithmetic, BLX-alpha and SBX create new real values and need
+// real-valued genes.
+
+use rand::Rng;
+
+use crate::genome::Genome;
+
+pub trait Crossover<G> {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G);
+}
+
+fn check_dimensions<G: Genome>(first: &G, second: &G) {
+    assert_eq!(
+        first.dimension(),
+        second.dimension(),
+        "Both parents must have the same dimension"
+    );
+}
+
+// Exchanges the genes in `range` between the two children
+fn swap_genes<G: Genome>(first: &mut G, second: &mut G, range: std::ops::Range<usize>) {
+    first.genes_mut()[range.clone()].swap_with_slice(&mut second.genes_mut()[range]);
+}
+
+// Exchanges everything after a single random cut point
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OnePointCrossover;
+
+impl<G: Genome> Crossover<G> for OnePointCrossover {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G) {
+        check_dimensions(first, second);
+        let (mut a, mut b) = (first.clone(), second.clone());
+        let n = a.dimension();
+        if n > 1 {
+            let cut = rng.gen_range(1..n);
+            swap_genes(&mut a, &mut b, cut..n);
+        }
+        (a, b)
+    }
+}
+
+// Exchanges the segment between two random cut points
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TwoPointCrossover;
+
+impl<G: Genome> Crossover<G> for TwoPointCrossover {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G) {
+        check_dimensions(first, second);
+        let (mut a, mut b) = (first.clone(), second.clone());
+        let n = a.dimension();
+        if n > 1 {
+            let i = rng.gen_range(0..n);
+            let j = rng.gen_range(0..n);
+            let (start, end) = if i <= j { (i, j + 1) } else { (j, i + 1) };
+            swap_genes(&mut a, &mut b, start..end);
+        }
+        (a, b)
+    }
+}
+
+// Exchanges every gene independently with probability `swap_probability`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UniformCrossover {
+    swap_probability: f64,
+}
+
+impl UniformCrossover {
+    pub fn new(swap_probability: f64) -> UniformCrossover {
+        UniformCrossover { swap_probability }
+    }
+}
+
+impl Default for UniformCrossover {
+    fn default() -> Self {
+        UniformCrossover::new(0.5)
+    }
+}
+
+impl<G: Genome> Crossover<G> for UniformCrossover {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G) {
+        check_dimensions(first, second);
+        let (mut a, mut b) = (first.clone(), second.clone());
+        for (x, y) in a.genes_mut().iter_mut().zip(b.genes_mut()) {
+            if rng.gen::<f64>() < self.swap_probability {
+                std::mem::swap(x, y);
+            }
+        }
+        (a, b)
+    }
+}
+
+// Weighted averages alpha x + (1 - alpha) y and (1 - alpha) x + alpha y. The
+// weight is drawn uniformly for every crossover unless it is fixed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArithmeticCrossover {
+    weight: Option<f64>,
+}
+
+impl ArithmeticCrossover {
+    pub fn new() -> ArithmeticCrossover {
+        ArithmeticCrossover { weight: None }
+    }
+
+    pub fn with_weight(weight: f64) -> ArithmeticCrossover {
+        assert!(
+            (0.0..=1.0).contains(&weight),
+            "The weight must be in [0, 1]"
+        );
+        ArithmeticCrossover {
+            weight: Some(weight),
+        }
+    }
+}
+
+impl Default for ArithmeticCrossover {
+    fn default() -> Self {
+        ArithmeticCrossover::new()
+    }
+}
+
+impl<G: Genome<Gene = f64>> Crossover<G> for ArithmeticCrossover {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G) {
+        check_dimensions(first, second);
+        let alpha = self.weight.unwrap_or_else(|| rng.gen());
+        let (mut a, mut b) = (first.clone(), second.clone());
+        for (x, y) in a.genes_mut().iter_mut().zip(b.genes_mut()) {
+            let (u, v) = (*x, *y);
+            *x = alpha * u + (1.0 - alpha) * v;
+            *y = (1.0 - alpha) * u + alpha * v;
+        }
+        (a, b)
+    }
+}
+
+// Blend crossover: every child gene is drawn uniformly from the interval
+// spanned by the parents, extended by `alpha` times its width on both sides
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlxAlphaCrossover {
+    alpha: f64,
+}
+
+impl BlxAlphaCrossover {
+    pub fn new(alpha: f64) -> BlxAlphaCrossover {
+        assert!(alpha >= 0.0, "alpha must not be negative");
+        BlxAlphaCrossover { alpha }
+    }
+}
+
+impl Default for BlxAlphaCrossover {
+    fn default() -> Self {
+        BlxAlphaCrossover::new(0.5)
+    }
+}
+
+impl<G: Genome<Gene = f64>> Crossover<G> for BlxAlphaCrossover {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G) {
+        check_dimensions(first, second);
+        let (mut a, mut b) = (first.clone(), second.clone());
+        for (x, y) in a.genes_mut().iter_mut().zip(b.genes_mut()) {
+            let (low, high) = (x.min(*y), x.max(*y));
+            let extension = self.alpha * (high - low);
+            let (low, high) = (low - extension, high + extension);
+            if low < high {
+                *x = rng.gen_range(low..high);
+                *y = rng.gen_range(low..high);
+            }
+        }
+        (a, b)
+    }
+}
+
+// Simulated binary crossover (Deb and Agrawal). Larger distribution indices
+// `eta` keep the children closer to their parents.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SbxCrossover {
+    eta: f64,
+}
+
+impl SbxCrossover {
+    pub fn new(eta: f64) -> SbxCrossover {
+        assert!(eta >= 0.0, "The distribution index must not be negative");
+        SbxCrossover { eta }
+    }
+}
+
+impl Default for SbxCrossover {
+    fn default() -> Self {
+        SbxCrossover::new(15.0)
+    }
+}
+
+impl<G: Genome<Gene = f64>> Crossover<G> for SbxCrossover {
+    fn crossover<R: Rng + ?Sized>(&self, first: &G, second: &G, rng: &mut R) -> (G, G) {
+        check_dimensions(first, second);
+        let (mut a, mut b) = (first.clone(), second.clone());
+        for (x, y) in a.genes_mut().iter_mut().zip(b.genes_mut()) {
+            let u: f64 = rng.gen();
+            let beta = if u <= 0.5 {
+                (2.0 * u).powf(1.0 / (self.eta + 1.0))
+            } else {
+                (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (self.eta + 1.0))
+            };
+            let (u, v) = (*x, *y);
+            *x = 0.5 * ((1.0 + beta) * u + (1.0 - beta) * v);
+            *y = 0.5 * ((1.0 - beta) * u + (1.0 + beta) * v);
+        }
+        (a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_positional_crossovers_exchange_genes() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let first = vec![0, 0, 0, 0, 0, 0];
+        let second = vec![1, 1, 1, 1, 1, 1];
+
+        for _ in 0..20 {
+            let (a, b) = OnePointCrossover.crossover(&first, &second, &mut rng);
+            assert_eq!(a[0], 0);
+            assert_eq!(a[5], 1);
+            let cut = a.iter().position(|gene| *gene == 1).unwrap();
+            assert!(a[cut..].iter().all(|gene| *gene == 1));
+
+            let (c, d) = TwoPointCrossover.crossover(&first, &second, &mut rng);
+            let (e, f) = UniformCrossover::default().crossover(&first, &second, &mut rng);
+            // Every position keeps one gene of each parent
+            for (x, y) in [(&a, &b), (&c, &d), (&e, &f)] {
+                assert!(x.iter().zip(y.iter()).all(|(x, y)| x + y == 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_real_crossovers() {
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        let first = vec![0.0, 1.0, -2.0];
+        let second = vec![1.0, 1.0, 2.0];
+
+        let (a, b) = ArithmeticCrossover::with_weight(0.25).crossover(&first, &second, &mut rng);
+        assert_eq!(a, vec![0.75, 1.0, 1.0]);
+        assert_eq!(b, vec![0.25, 1.0, -1.0]);
+
+        for _ in 0..20 {
+            let (a, b) = BlxAlphaCrossover::new(0.5).crossover(&first, &second, &mut rng);
+            assert!(a[0] >= -0.5 && a[0] <= 1.5 && b[2] >= -4.0 && b[2] <= 4.0);
+            assert_eq!((a[1], b[1]), (1.0, 1.0));
+
+            // SBX children are placed symmetrically around the parents' mean
+            let (a, b) = SbxCrossover::default().crossover(&first, &second, &mut rng);
+            for i in 0..3 {
+                assert!((a[i] + b[i] - first[i] - second[i]).abs() < 1e-12);
+            }
+        }
+    }
+}
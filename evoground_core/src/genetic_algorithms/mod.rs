@@ -0,0 +1,332 @@
+// Module for genetic algorithms
+//
+// A generational GA replaces the whole population every generation. The
+// selector fills a mating pool, consecutive pairs from the pool are crossed
+// over with probability `crossover_rate` and every child is mutated. The best
+// `elitism` individuals of the old population survive unchanged. Selection,
+// crossover and mutation are the same traits used by the evolution strategies,
+// so any combination of operators can be plugged in. As in the evolution
+// strategies the candidates are evaluated with the objective of the selector.
+//
+// With ask/tell the first ask returns the initial population. Every later ask
+// returns the offspring of one generation, the elite keeps its known fitness.
+
+mod crossover;
+
+pub use crossover::{
+    ArithmeticCrossover, BlxAlphaCrossover, Crossover, OnePointCrossover, SbxCrossover,
+    TwoPointCrossover, UniformCrossover,
+};
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::evaluation::Evaluated;
+use crate::evolution_strategies::{Mutate, Select};
+use crate::genome::Genome;
+use crate::objective::OptimizationDirection;
+use crate::optimizer::{check_tell, AskTell, Optimizer, TellError};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeneticAlgorithm<G, C, M, S> {
+    population: Vec<G>,
+    fitness: Vec<f64>,
+    crossover: C,
+    mutator: M,
+    selector: S,
+    crossover_rate: f64,
+    elitism: usize,
+    best: Option<Evaluated<G>>,
+    // The elite of the next generation and the offspring waiting for their fitness
+    elite: Vec<Evaluated<G>>,
+    pending: Vec<G>,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
+}
+
+impl<G: Genome, C: Crossover<G>, M: Mutate<G>, S: Select<G>> GeneticAlgorithm<G, C, M, S> {
+    pub fn new(initial_population: Vec<G>, crossover: C, mutator: M, selector: S) -> Self {
+        assert!(
+            initial_population.len() >= 2,
+            "A genetic algorithm needs at least two individuals"
+        );
+
+        GeneticAlgorithm {
+            population: initial_population,
+            fitness: Vec::new(),
+            crossover,
+            mutator,
+            selector,
+            crossover_rate: 0.9,
+            elitism: 1,
+            best: None,
+            elite: Vec::new(),
+            pending: Vec::new(),
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn with_crossover_rate(mut self, crossover_rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&crossover_rate),
+            "The crossover rate must be a probability"
+        );
+        self.crossover_rate = crossover_rate;
+        self
+    }
+
+    // Number of best individuals copied unchanged into the next generation
+    pub fn with_elitism(mut self, elitism: usize) -> Self {
+        assert!(
+            elitism < self.population.len(),
+            "Elitism must leave room for offspring"
+        );
+        self.elitism = elitism;
+        self
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        if self.fitness.is_empty() {
+            self.evaluate_pending(on_evaluation);
+        }
+        self.evaluate_pending(on_evaluation);
+    }
+
+    fn evaluate_pending(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.selector.evaluate(&self.pending);
+        for (individual, fitness) in self.pending.iter().zip(&fitness) {
+            on_evaluation(individual, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    pub fn ask(&mut self) -> &[G] {
+        if !self.pending.is_empty() {
+            return &self.pending;
+        }
+        if self.fitness.is_empty() {
+            self.pending = self.population.clone();
+            return &self.pending;
+        }
+        let size = self.population.len();
+
+        // The elite of the current population, best first
+        let direction = self.selector.direction();
+        let mut order: Vec<usize> = (0..size).collect();
+        order.sort_by(|a, b| direction.compare(self.fitness[*a], self.fitness[*b]));
+        self.elite = order[..self.elitism]
+            .iter()
+            .map(|index| Evaluated::new(self.population[*index].clone(), self.fitness[*index]))
+            .collect();
+
+        let mut pool: Vec<G> = self
+            .selector
+            .select_evaluated(&self.population, &self.fitness, &mut self.rng)
+            .iter()
+            .map(|index| self.population[*index].clone())
+            .collect();
+        assert!(!pool.is_empty(), "The selector returned no parents");
+        pool.shuffle(&mut self.rng);
+
+        let offspring = size - self.elitism;
+        let mut i = 0;
+        while self.pending.len() < offspring {
+            let first = &pool[i % pool.len()];
+            let second = &pool[(i + 1) % pool.len()];
+            i += 2;
+
+            let (mut a, mut b) = if self.rng.gen::<f64>() < self.crossover_rate {
+                self.crossover.crossover(first, second, &mut self.rng)
+            } else {
+                (first.clone(), second.clone())
+            };
+            self.mutator.mutate(&mut a, &mut self.rng);
+            self.pending.push(a);
+            if self.pending.len() < offspring {
+                self.mutator.mutate(&mut b, &mut self.rng);
+                self.pending.push(b);
+            }
+        }
+        &self.pending
+    }
+
+    // Telling the fitness of the initial population does not count as a generation
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.pending, fitness)?;
+        self.evaluations += fitness.len();
+
+        let direction = self.selector.direction();
+        for (individual, fitness) in self.pending.iter().zip(fitness) {
+            if self
+                .best
+                .as_ref()
+                .is_none_or(|best| direction.is_better(*fitness, best.fitness))
+            {
+                self.best = Some(Evaluated::new(individual.clone(), *fitness));
+            }
+        }
+
+        if self.fitness.is_empty() {
+            self.pending.clear();
+            self.fitness = fitness.to_vec();
+            return Ok(());
+        }
+        let (elite, elite_fitness): (Vec<G>, Vec<f64>) = self
+            .elite
+            .drain(..)
+            .map(|individual| (individual.genome, individual.fitness))
+            .unzip();
+        self.population = elite;
+        self.population.append(&mut self.pending);
+        self.fitness = elite_fitness;
+        self.fitness.extend_from_slice(fitness);
+        self.generation += 1;
+        Ok(())
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    pub fn population(&self) -> &[G] {
+        &self.population
+    }
+
+    // The best individual found so far, or the first one of the initial population
+    pub fn best_individual(&self) -> &G {
+        self.best
+            .as_ref()
+            .map_or(&self.population[0], |best| &best.genome)
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best.as_ref().map(|best| best.fitness)
+    }
+
+    pub fn direction(&self) -> OptimizationDirection {
+        self.selector.direction()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+impl<G: Genome, C: Crossover<G>, M: Mutate<G>, S: Select<G>> Optimizer<G>
+    for GeneticAlgorithm<G, C, M, S>
+{
+    fn step(&mut self) {
+        GeneticAlgorithm::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        GeneticAlgorithm::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        GeneticAlgorithm::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        GeneticAlgorithm::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        GeneticAlgorithm::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        GeneticAlgorithm::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        GeneticAlgorithm::population(self)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        &self.fitness
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        GeneticAlgorithm::direction(self)
+    }
+}
+
+impl<G: Genome, C: Crossover<G>, M: Mutate<G>, S: Select<G>> AskTell<G>
+    for GeneticAlgorithm<G, C, M, S>
+{
+    fn ask(&mut self) -> &[G] {
+        GeneticAlgorithm::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        GeneticAlgorithm::tell(self, fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::Sphere;
+    use crate::evolution_strategies::{BitFlipMutator, GaussianMutator, SimpleSelector};
+    use crate::genome::BitString;
+    use crate::testing::random_population;
+
+    #[test]
+    fn test_genetic_algorithm_one_max() {
+        let one_max = |x: &BitString| -> f64 { x.iter().filter(|bit| **bit).count() as f64 };
+        let population = vec![vec![false; 32]; 20];
+
+        let mut ga = GeneticAlgorithm::new(
+            population,
+            UniformCrossover::default(),
+            BitFlipMutator::new(1.0 / 32.0),
+            SimpleSelector::new(10, one_max),
+        )
+        .with_seed(5);
+        ga.run(200);
+
+        assert_eq!(ga.best_fitness(), Some(32.0));
+        assert_eq!(ga.population().len(), 20);
+    }
+
+    #[test]
+    fn test_genetic_algorithm_real_valued() {
+        let population = random_population(30, 4, 2);
+
+        let mut ga = GeneticAlgorithm::new(
+            population,
+            SbxCrossover::default(),
+            GaussianMutator::new(0.25, 0.1),
+            SimpleSelector::new(15, Sphere),
+        )
+        .with_elitism(2)
+        .with_seed(2);
+        ga.run(300);
+
+        assert!(ga.best_fitness().unwrap() < 1e-2);
+        // The elite is not evaluated again
+        assert_eq!(ga.evaluations(), 30 + 300 * 28);
+    }
+}
@@ -0,0 +1,73 @@
+// Module for genome representations
+//
+// A genome is the encoded form of an individual that the operators work on.
+// Every genome exposes its genes as a slice, so operators can be written once
+// for scalars, growable vectors and fixed-size arrays alike.
+
+pub trait Genome: Clone {
+    type Gene;
+
+    fn genes(&self) -> &[Self::Gene];
+    fn genes_mut(&mut self) -> &mut [Self::Gene];
+
+    fn dimension(&self) -> usize {
+        self.genes().len()
+    }
+
+    // Endogenous strategy parameters (e.g. mutation step sizes) that travel
+    // with the genome. Plain genomes carry none.
+    fn strategy_parameters(&self) -> &[f64] {
+        &[]
+    }
+
+    fn strategy_parameters_mut(&mut self) -> &mut [f64] {
+        &mut []
+    }
+}
+
+// A bitstring is simply a vector of boolean genes.
+pub type BitString = Vec<bool>;
+
+macro_rules! impl_scalar_genome {
+    ($($scalar:ty),*) => {
+        $(
+            impl Genome for $scalar {
+                type Gene = $scalar;
+
+                fn genes(&self) -> &[$scalar] {
+                    std::slice::from_ref(self)
+                }
+
+                fn genes_mut(&mut self) -> &mut [$scalar] {
+                    std::slice::from_mut(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_genome!(f64, i64, bool);
+
+impl<T: Clone> Genome for Vec<T> {
+    type Gene = T;
+
+    fn genes(&self) -> &[T] {
+        self
+    }
+
+    fn genes_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T: Clone, const N: usize> Genome for [T; N] {
+    type Gene = T;
+
+    fn genes(&self) -> &[T] {
+        self
+    }
+
+    fn genes_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
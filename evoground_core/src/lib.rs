@@ -1,4 +1,20 @@
+pub mod benchmarks;
+pub mod bounds;
+pub mod constraints;
+pub mod differential_evolution;
+pub mod evaluation;
 pub mod evolution_strategies;
+pub mod genetic_algorithms;
+pub mod genome;
+pub mod objective;
+pub mod observer;
+pub mod optimizer;
+pub mod particle_swarm;
+pub mod result;
+pub mod termination;
+
+#[cfg(test)]
+mod testing;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
@@ -0,0 +1,318 @@
+// Module for objective functions
+//
+// An objective assigns a fitness value to a genome. It is implemented for any
+// closure taking the genome by reference, including closures that capture data,
+// and can be implemented by user structs that need mutable state (counters,
+// caches, simulator handles) between evaluations.
+//
+// Strategies evaluate their candidates in batches. With the `parallel` feature
+// wrapping a thread safe closure in `Parallel` spreads every batch over the
+// rayon thread pool. The fitness values come back in the order of the batch,
+// so results under a fixed seed do not depend on the number of threads.
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OptimizationDirection {
+    Minimize,
+    #[default]
+    Maximize,
+}
+
+impl OptimizationDirection {
+    // Returns true if fitness `a` is strictly better than fitness `b`
+    pub fn is_better(&self, a: f64, b: f64) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+
+    // Orders two fitness values so that the better one comes first. NaN is
+    // always considered worse than any other value.
+    pub fn compare(&self, a: f64, b: f64) -> Ordering {
+        match (a.is_nan(), b.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => match self {
+                OptimizationDirection::Minimize => a.partial_cmp(&b).unwrap(),
+                OptimizationDirection::Maximize => b.partial_cmp(&a).unwrap(),
+            },
+        }
+    }
+
+    // The worst possible fitness in this direction
+    pub fn worst(&self) -> f64 {
+        match self {
+            OptimizationDirection::Minimize => f64::INFINITY,
+            OptimizationDirection::Maximize => f64::NEG_INFINITY,
+        }
+    }
+}
+
+pub trait Objective<G> {
+    fn evaluate(&mut self, individual: &G) -> f64;
+
+    // The fitness of every individual, in order
+    fn evaluate_batch(&mut self, individuals: &[G]) -> Vec<f64> {
+        individuals
+            .iter()
+            .map(|individual| self.evaluate(individual))
+            .collect()
+    }
+
+    // Objectives are maximized unless stated otherwise
+    fn direction(&self) -> OptimizationDirection {
+        OptimizationDirection::Maximize
+    }
+}
+
+impl<G, F> Objective<G> for F
+where
+    F: FnMut(&G) -> f64,
+{
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        self(individual)
+    }
+}
+
+// Wraps an objective so that it is minimized, e.g. `Minimize(|x: &f64| x * x)`
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Minimize<O>(pub O);
+
+impl<G, O: Objective<G>> Objective<G> for Minimize<O> {
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        self.0.evaluate(individual)
+    }
+
+    fn evaluate_batch(&mut self, individuals: &[G]) -> Vec<f64> {
+        self.0.evaluate_batch(individuals)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        OptimizationDirection::Minimize
+    }
+}
+
+// Wraps an objective so that it is maximized
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Maximize<O>(pub O);
+
+impl<G, O: Objective<G>> Objective<G> for Maximize<O> {
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        self.0.evaluate(individual)
+    }
+
+    fn evaluate_batch(&mut self, individuals: &[G]) -> Vec<f64> {
+        self.0.evaluate_batch(individuals)
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        OptimizationDirection::Maximize
+    }
+}
+
+// Evaluates batches concurrently, e.g. `Minimize(Parallel(|x: &Vec<f64>| ...))`.
+// The closure is shared between threads, so it cannot mutate captured state.
+#[cfg(feature = "parallel")]
+#[derive(Clone, Copy)]
+pub struct Parallel<F>(pub F);
+
+#[cfg(feature = "parallel")]
+impl<G: Sync, F: Fn(&G) -> f64 + Sync> Objective<G> for Parallel<F> {
+    fn evaluate(&mut self, individual: &G) -> f64 {
+        (self.0)(individual)
+    }
+
+    fn evaluate_batch(&mut self, individuals: &[G]) -> Vec<f64> {
+        use rayon::prelude::*;
+
+        individuals
+            .par_iter()
+            .map(|individual| (self.0)(individual))
+            .collect()
+    }
+}
+
+// Placeholder for objectives evaluated outside of the process through the
+// ask/tell interface. It only carries the direction and panics if a strategy
+// tries to evaluate it directly, e.g. by calling `step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct External(pub OptimizationDirection);
+
+impl External {
+    pub fn minimize() -> External {
+        External(OptimizationDirection::Minimize)
+    }
+
+    pub fn maximize() -> External {
+        External(OptimizationDirection::Maximize)
+    }
+}
+
+impl<G> Objective<G> for External {
+    fn evaluate(&mut self, _individual: &G) -> f64 {
+        panic!("External objectives can only be evaluated through ask and tell")
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere};
+    use crate::differential_evolution::DifferentialEvolution;
+    use crate::evolution_strategies::{
+        CmaEs, GaussianMutator, MuPlusLambdaStrategy, SimpleSelector,
+    };
+    use crate::genetic_algorithms::{GeneticAlgorithm, SbxCrossover};
+    use crate::optimizer::AskTell;
+    use crate::particle_swarm::ParticleSwarm;
+    use crate::testing::random_population;
+
+    #[test]
+    fn test_external_only_carries_the_direction() {
+        let minimize = External::minimize();
+        let maximize = External::maximize();
+        assert_eq!(
+            Objective::<f64>::direction(&minimize),
+            OptimizationDirection::Minimize
+        );
+        assert_eq!(
+            Objective::<f64>::direction(&maximize),
+            OptimizationDirection::Maximize
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only be evaluated through ask and tell")]
+    fn test_external_cannot_be_evaluated() {
+        External::minimize().evaluate(&vec![1.0]);
+    }
+
+    #[test]
+    fn test_ask_tell_with_external_objective() {
+        let objective = External::minimize();
+        let start = vec![2.0; 3];
+        let population = random_population(10, 3, 2);
+
+        let mut strategies: Vec<Box<dyn AskTell<Vec<f64>>>> = vec![
+            Box::new(CmaEs::new(start.clone(), 1.0, objective).with_seed(2)),
+            Box::new(
+                MuPlusLambdaStrategy::new(
+                    population.clone(),
+                    20,
+                    GaussianMutator::new(1.0, 0.05),
+                    SimpleSelector::new(5, objective),
+                )
+                .with_seed(2),
+            ),
+            Box::new(
+                GeneticAlgorithm::new(
+                    population.clone(),
+                    SbxCrossover::default(),
+                    GaussianMutator::new(0.25, 0.1),
+                    SimpleSelector::new(5, objective),
+                )
+                .with_seed(2),
+            ),
+            Box::new(DifferentialEvolution::new(population.clone(), objective).with_seed(2)),
+            Box::new(ParticleSwarm::new(population, objective).with_seed(2)),
+        ];
+
+        for strategy in strategies.iter_mut() {
+            let mut best = f64::INFINITY;
+            for _ in 0..200 {
+                let fitness: Vec<f64> = strategy.ask().iter().map(|x| Sphere.value(x)).collect();
+                best = fitness.iter().fold(best, |best, f| best.min(*f));
+                strategy.tell(&fitness).unwrap();
+            }
+            assert!(best < 1e-2, "{}", best);
+        }
+    }
+
+    #[test]
+    fn test_direction_wrappers_forward_evaluation() {
+        let square = |x: &f64| x * x;
+        let individuals = [3.0, -1.0, 2.0];
+
+        assert_eq!(
+            Objective::<f64>::direction(&square),
+            OptimizationDirection::Maximize
+        );
+        let mut minimize = Minimize(square);
+        assert_eq!(
+            Objective::<f64>::direction(&minimize),
+            OptimizationDirection::Minimize
+        );
+        assert_eq!(minimize.evaluate(&3.0), 9.0);
+        assert_eq!(minimize.evaluate_batch(&individuals), vec![9.0, 1.0, 4.0]);
+
+        // Maximize turns a minimized objective around
+        let mut maximize = Maximize(Minimize(square));
+        assert_eq!(
+            Objective::<f64>::direction(&maximize),
+            OptimizationDirection::Maximize
+        );
+        assert_eq!(maximize.evaluate_batch(&individuals), vec![9.0, 1.0, 4.0]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_evaluation_is_deterministic() {
+        let sequential = Sphere;
+        let parallel = Minimize(Parallel(|x: &Vec<f64>| Sphere.value(x)));
+        let population = random_population(20, 4, 6);
+
+        let mut a = CmaEs::new(vec![2.0; 4], 1.0, sequential).with_seed(6);
+        let mut b = CmaEs::new(vec![2.0; 4], 1.0, parallel).with_seed(6);
+        a.run(30);
+        b.run(30);
+        assert_eq!(a.population(), b.population());
+        assert_eq!(a.best_fitness(), b.best_fitness());
+
+        let mut a = DifferentialEvolution::new(population.clone(), sequential).with_seed(6);
+        let mut b = DifferentialEvolution::new(population.clone(), parallel).with_seed(6);
+        a.run(30);
+        b.run(30);
+        assert_eq!(a.population(), b.population());
+
+        let mut a = MuPlusLambdaStrategy::new(
+            population.clone(),
+            40,
+            GaussianMutator::new(1.0, 0.1),
+            SimpleSelector::new(10, sequential),
+        )
+        .with_seed(6);
+        let mut b = MuPlusLambdaStrategy::new(
+            population,
+            40,
+            GaussianMutator::new(1.0, 0.1),
+            SimpleSelector::new(10, parallel),
+        )
+        .with_seed(6);
+        a.run(30);
+        b.run(30);
+        assert_eq!(a.population(), b.population());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_batches_keep_their_order() {
+        let individuals = random_population(1000, 3, 7);
+        let expected: Vec<f64> = individuals.iter().map(|x| Sphere.value(x)).collect();
+
+        let mut objective = Parallel(|x: &Vec<f64>| Sphere.value(x));
+        assert_eq!(objective.evaluate_batch(&individuals), expected);
+        assert_eq!(objective.evaluate(&individuals[0]), expected[0]);
+        assert_eq!(
+            Objective::<Vec<f64>>::direction(&objective),
+            OptimizationDirection::Maximize
+        );
+    }
+}
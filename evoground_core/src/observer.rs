@@ -0,0 +1,270 @@
+// Observers for monitoring runs
+//
+// `Optimizer::run_observed` calls the hooks of an observer while it drives the
+// optimizer: once before the first generation, for every evaluated candidate,
+// whenever the best fitness improves, after every generation and once with the
+// final result. Returning `Control::Stop` from `on_generation` ends the run
+// early. A pair of observers is itself an observer that calls both.
+
+use crate::result::{GenerationStats, RunResult};
+use crate::termination::Status;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+pub trait Observer<G> {
+    fn on_start(&mut self, _status: &Status<G>) {}
+
+    // A candidate and the fitness the objective returned for it
+    fn on_evaluation(&mut self, _individual: &G, _fitness: f64) {}
+
+    // The new best individual found so far
+    fn on_improvement(&mut self, _individual: &G, _fitness: f64) {}
+
+    fn on_generation(&mut self, _status: &Status<G>) -> Control {
+        Control::Continue
+    }
+
+    fn on_finish(&mut self, _result: &RunResult<G>) {}
+}
+
+// Observes nothing
+impl<G> Observer<G> for () {}
+
+impl<G, A: Observer<G>, B: Observer<G>> Observer<G> for (A, B) {
+    fn on_start(&mut self, status: &Status<G>) {
+        self.0.on_start(status);
+        self.1.on_start(status);
+    }
+
+    fn on_evaluation(&mut self, individual: &G, fitness: f64) {
+        self.0.on_evaluation(individual, fitness);
+        self.1.on_evaluation(individual, fitness);
+    }
+
+    fn on_improvement(&mut self, individual: &G, fitness: f64) {
+        self.0.on_improvement(individual, fitness);
+        self.1.on_improvement(individual, fitness);
+    }
+
+    // Stops if either observer asks to, after both have seen the generation
+    fn on_generation(&mut self, status: &Status<G>) -> Control {
+        let first = self.0.on_generation(status);
+        let second = self.1.on_generation(status);
+        if first == Control::Stop || second == Control::Stop {
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    }
+
+    fn on_finish(&mut self, result: &RunResult<G>) {
+        self.0.on_finish(result);
+        self.1.on_finish(result);
+    }
+}
+
+// Prints a progress line to stdout every `interval` generations and a summary
+// at the end of the run
+pub struct ProgressPrinter {
+    interval: usize,
+}
+
+impl ProgressPrinter {
+    pub fn new() -> Self {
+        ProgressPrinter { interval: 1 }
+    }
+
+    pub fn with_interval(mut self, interval: usize) -> Self {
+        assert!(interval > 0, "The interval must be positive");
+        self.interval = interval;
+        self
+    }
+}
+
+impl Default for ProgressPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G> Observer<G> for ProgressPrinter {
+    fn on_generation(&mut self, status: &Status<G>) -> Control {
+        if status.generation.is_multiple_of(self.interval) {
+            println!("{}", progress_line(status));
+        }
+        Control::Continue
+    }
+
+    fn on_finish(&mut self, result: &RunResult<G>) {
+        println!(
+            "finished after {} generations and {} evaluations in {:?}: {}",
+            result.generations, result.evaluations, result.elapsed, result.termination
+        );
+    }
+}
+
+fn progress_line<G>(status: &Status<G>) -> String {
+    let mut line = format!(
+        "generation {:>6}  evaluations {:>8}",
+        status.generation, status.evaluations
+    );
+    if let Some(fitness) = status.best_fitness {
+        line += &format!("  best {:.6e}", fitness);
+    }
+    if let Some(step_size) = status.step_size {
+        line += &format!("  step size {:.3e}", step_size);
+    }
+    line
+}
+
+// Records the statistics of every observed generation
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    records: Vec<GenerationStats>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    pub fn records(&self) -> &[GenerationStats] {
+        &self.records
+    }
+
+    pub fn into_records(self) -> Vec<GenerationStats> {
+        self.records
+    }
+}
+
+impl<G> Observer<G> for History {
+    fn on_generation(&mut self, status: &Status<G>) -> Control {
+        self.records.extend(GenerationStats::new(
+            status.generation,
+            status.evaluations,
+            status.population_fitness,
+            status.direction,
+            status.step_size,
+        ));
+        Control::Continue
+    }
+}
+
+// Stops the run after the first generation for which the predicate holds
+pub struct EarlyStopping<F> {
+    predicate: F,
+}
+
+impl<F> EarlyStopping<F> {
+    pub fn new(predicate: F) -> Self {
+        EarlyStopping { predicate }
+    }
+}
+
+impl<G, F: FnMut(&Status<G>) -> bool> Observer<G> for EarlyStopping<F> {
+    fn on_generation(&mut self, status: &Status<G>) -> Control {
+        if (self.predicate)(status) {
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere};
+    use crate::differential_evolution::DifferentialEvolution;
+    use crate::evolution_strategies::CmaEs;
+    use crate::objective::OptimizationDirection;
+    use crate::optimizer::Optimizer;
+    use crate::termination::{MaxGenerations, TerminationReason};
+
+    #[derive(Default)]
+    struct Counter {
+        starts: usize,
+        evaluations: usize,
+        improvements: Vec<f64>,
+        generations: usize,
+        finishes: usize,
+    }
+
+    impl Observer<Vec<f64>> for Counter {
+        fn on_start(&mut self, _status: &Status<Vec<f64>>) {
+            self.starts += 1;
+        }
+
+        fn on_evaluation(&mut self, individual: &Vec<f64>, fitness: f64) {
+            assert_eq!(fitness, Sphere.value(individual));
+            self.evaluations += 1;
+        }
+
+        fn on_improvement(&mut self, _individual: &Vec<f64>, fitness: f64) {
+            self.improvements.push(fitness);
+        }
+
+        fn on_generation(&mut self, _status: &Status<Vec<f64>>) -> Control {
+            self.generations += 1;
+            Control::Continue
+        }
+
+        fn on_finish(&mut self, _result: &RunResult<Vec<f64>>) {
+            self.finishes += 1;
+        }
+    }
+
+    #[test]
+    fn test_observer_hooks() {
+        let population: Vec<Vec<f64>> = (0..8).map(|i| vec![i as f64 - 4.0, 2.0]).collect();
+        let mut de = DifferentialEvolution::new(population, Sphere).with_seed(1);
+        let mut observer = (Counter::default(), History::new());
+
+        let result = de.run_observed(&mut MaxGenerations::new(30), &mut observer);
+        let (counter, history) = observer;
+        assert_eq!(counter.starts, 1);
+        assert_eq!(counter.finishes, 1);
+        assert_eq!(counter.generations, 30);
+        assert_eq!(counter.evaluations, result.evaluations);
+        assert_eq!(history.records().len(), 30);
+
+        // Improvements are strictly decreasing and end at the best fitness
+        assert!(counter.improvements.windows(2).all(|w| w[1] < w[0]));
+        assert_eq!(counter.improvements.last().copied(), result.best_fitness);
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let mut strategy = CmaEs::new(vec![2.0; 3], 1.0, Sphere).with_seed(2);
+        let mut observer = EarlyStopping::new(|status: &Status<Vec<f64>>| {
+            status.best_fitness.is_some_and(|fitness| fitness < 1e-4)
+        });
+
+        let result = strategy.run_observed(&mut MaxGenerations::new(1000), &mut observer);
+        assert_eq!(result.termination, TerminationReason::Observer);
+        assert!(result.best_fitness.unwrap() < 1e-4);
+        assert!(result.generations < 1000);
+    }
+
+    #[test]
+    fn test_progress_line() {
+        let population = vec![vec![0.0]];
+        let status = Status {
+            generation: 12,
+            evaluations: 120,
+            best_fitness: Some(0.5),
+            step_size: None,
+            population: &population,
+            population_fitness: &[0.5],
+            direction: OptimizationDirection::Minimize,
+        };
+        assert_eq!(
+            progress_line(&status),
+            "generation     12  evaluations      120  best 5.000000e-1"
+        );
+    }
+}
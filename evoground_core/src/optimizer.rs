@@ -0,0 +1,426 @@
+// Common interface of all optimization algorithms
+//
+// Every strategy keeps its inherent methods, the traits only forward to them
+// so that harness code can be written once and pointed at any algorithm.
+//
+// With ask/tell the objective is evaluated by the caller instead of the
+// strategy: `ask` hands out the candidates of the next generation and `tell`
+// completes the generation with their fitness values. Calling `step` is the
+// same as asking, evaluating every candidate with the strategy's objective
+// and telling the results.
+//
+// With the `serde` feature every strategy can be serialized between
+// generations, together with its operators and random number generator, and
+// resumed to continue exactly as if it had never stopped. The objective is
+// saved with the strategy, so checkpointed strategies need a named objective
+// type instead of a closure.
+
+use std::fmt;
+use std::time::Instant;
+
+use crate::objective::OptimizationDirection;
+use crate::observer::{Control, History, Observer};
+use crate::result::RunResult;
+use crate::termination::{Status, Termination, TerminationReason};
+
+pub trait Optimizer<G> {
+    // Advances the optimizer by one generation
+    fn step(&mut self);
+
+    // Called by the `run_*` methods before they query the optimizer. Wrappers
+    // that build their strategy lazily build it here.
+    fn start(&mut self) {}
+
+    // Like `step`, and reports every evaluated candidate with the fitness the
+    // objective returned for it. Optimizers that cannot do so just step.
+    fn step_observed(&mut self, _on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.step();
+    }
+
+    fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    // The best individual found so far. Before the first generation this is
+    // the initial individual or a member of the initial population.
+    fn best_individual(&self) -> &G;
+
+    // None if the optimizer has not evaluated anything yet or does not know
+    // the fitness of its individuals
+    fn best_fitness(&self) -> Option<f64>;
+
+    // Number of objective evaluations used so far
+    fn evaluations(&self) -> usize;
+
+    fn generation(&self) -> usize;
+
+    // The individuals the optimizer currently works with
+    fn population(&self) -> &[G];
+
+    // Fitness of `population()` in the same order, empty while it is unknown
+    fn population_fitness(&self) -> &[f64];
+
+    fn direction(&self) -> OptimizationDirection;
+
+    // The global step size of strategies that adapt one
+    fn step_size(&self) -> Option<f64> {
+        None
+    }
+
+    // Steps until the termination criterion fires, which is checked before
+    // every generation. A criterion that never fires runs forever.
+    fn run_until(&mut self, termination: &mut dyn Termination<G>) -> RunResult<G>
+    where
+        G: Clone,
+    {
+        drive(self, termination, &mut ())
+    }
+
+    // Like `run_until`, and records statistics of every generation
+    fn run_with_history(&mut self, termination: &mut dyn Termination<G>) -> RunResult<G>
+    where
+        G: Clone,
+    {
+        let mut history = History::new();
+        let mut result = drive(self, termination, &mut history);
+        result.history = Some(history.into_records());
+        result
+    }
+
+    // Like `run_until`, and reports the progress of the run to the observer,
+    // which may also end it early
+    fn run_observed(
+        &mut self,
+        termination: &mut dyn Termination<G>,
+        observer: &mut dyn Observer<G>,
+    ) -> RunResult<G>
+    where
+        G: Clone,
+    {
+        drive(self, termination, observer)
+    }
+}
+
+fn status<G, O: Optimizer<G> + ?Sized>(optimizer: &mut O) -> Status<'_, G> {
+    let best_fitness = optimizer.best_fitness();
+    Status {
+        generation: optimizer.generation(),
+        evaluations: optimizer.evaluations(),
+        best_fitness,
+        step_size: optimizer.step_size(),
+        population: optimizer.population(),
+        population_fitness: optimizer.population_fitness(),
+        direction: optimizer.direction(),
+    }
+}
+
+fn drive<G: Clone, O: Optimizer<G> + ?Sized>(
+    optimizer: &mut O,
+    termination: &mut dyn Termination<G>,
+    observer: &mut dyn Observer<G>,
+) -> RunResult<G> {
+    let started = Instant::now();
+    optimizer.start();
+    termination.start();
+    observer.on_start(&status(optimizer));
+
+    let reason = loop {
+        if let Some(reason) = termination.check(&status(optimizer)) {
+            break reason;
+        }
+
+        let previous = optimizer.best_fitness();
+        optimizer
+            .step_observed(&mut |individual, fitness| observer.on_evaluation(individual, fitness));
+        if let Some(fitness) = optimizer.best_fitness() {
+            let direction = optimizer.direction();
+            if previous.is_none_or(|previous| direction.is_better(fitness, previous)) {
+                observer.on_improvement(optimizer.best_individual(), fitness);
+            }
+        }
+
+        if observer.on_generation(&status(optimizer)) == Control::Stop {
+            break TerminationReason::Observer;
+        }
+    };
+
+    let result = RunResult {
+        best_individual: optimizer.best_individual().clone(),
+        best_fitness: optimizer.best_fitness(),
+        evaluations: optimizer.evaluations(),
+        generations: optimizer.generation(),
+        termination: reason,
+        elapsed: started.elapsed(),
+        history: None,
+    };
+    observer.on_finish(&result);
+    result
+}
+
+pub trait AskTell<G> {
+    // The candidates to evaluate. Asking again before telling returns the
+    // same candidates.
+    fn ask(&mut self) -> &[G];
+
+    // The fitness values of the asked candidates, in the same order
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TellError {
+    // `tell` was called without asking for candidates first
+    NothingAsked,
+    // The number of fitness values does not match the number of candidates
+    CountMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for TellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TellError::NothingAsked => write!(f, "no candidates have been asked for"),
+            TellError::CountMismatch { expected, actual } => write!(
+                f,
+                "expected {} fitness values for the asked candidates, got {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TellError {}
+
+// Checks a tell against the pending candidates
+pub(crate) fn check_tell<G>(pending: &[G], fitness: &[f64]) -> Result<(), TellError> {
+    if pending.is_empty() {
+        Err(TellError::NothingAsked)
+    } else if pending.len() != fitness.len() {
+        Err(TellError::CountMismatch {
+            expected: pending.len(),
+            actual: fitness.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere};
+    use crate::differential_evolution::DifferentialEvolution;
+    use crate::evolution_strategies::{
+        AdaptiveOnePlusOneStrategy, CmaEs, GaussianMutator, MuPlusLambdaStrategy,
+        OnePlusOneStrategy, SimpleSelector,
+    };
+    use crate::objective::External;
+    use crate::particle_swarm::ParticleSwarm;
+    use crate::testing::random_population;
+
+    #[test]
+    fn test_optimizers_are_interchangeable() {
+        let objective = Sphere;
+        let start = vec![2.0; 3];
+        let population = random_population(10, 3, 1);
+
+        let mut optimizers: Vec<Box<dyn Optimizer<Vec<f64>>>> = vec![
+            Box::new(
+                AdaptiveOnePlusOneStrategy::new(
+                    start.clone(),
+                    GaussianMutator::new(1.0, 1.0),
+                    objective,
+                )
+                .with_window(5)
+                .with_seed(1),
+            ),
+            Box::new(CmaEs::new(start.clone(), 1.0, objective).with_seed(1)),
+            Box::new(
+                MuPlusLambdaStrategy::new(
+                    population.clone(),
+                    20,
+                    GaussianMutator::new(1.0, 0.05),
+                    SimpleSelector::new(5, objective),
+                )
+                .with_seed(1),
+            ),
+            Box::new(DifferentialEvolution::new(population.clone(), objective).with_seed(1)),
+            Box::new(ParticleSwarm::new(population, objective).with_seed(1)),
+        ];
+
+        for optimizer in optimizers.iter_mut() {
+            optimizer.run(200);
+
+            assert_eq!(optimizer.generation(), 200);
+            assert!(optimizer.evaluations() > 0);
+            assert!(!optimizer.population().is_empty());
+            assert!(
+                Sphere.value(optimizer.best_individual()) < 1e-2,
+                "{:?}",
+                optimizer.best_individual()
+            );
+            if let Some(fitness) = optimizer.best_fitness() {
+                assert_eq!(fitness, Sphere.value(optimizer.best_individual()));
+            }
+            assert_eq!(
+                optimizer.population_fitness().len(),
+                optimizer.population().len()
+            );
+            for (x, fitness) in optimizer
+                .population()
+                .iter()
+                .zip(optimizer.population_fitness())
+            {
+                assert_eq!(*fitness, Sphere.value(x));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ask_tell_matches_step() {
+        let mut stepped = CmaEs::new(vec![2.0; 3], 1.0, Sphere).with_seed(4);
+        let mut told = CmaEs::new(vec![2.0; 3], 1.0, External::minimize()).with_seed(4);
+        for _ in 0..20 {
+            stepped.step();
+            let fitness: Vec<f64> = told.ask().iter().map(|x| Sphere.value(x)).collect();
+            told.tell(&fitness).unwrap();
+        }
+        assert_eq!(stepped.mean(), told.mean());
+        assert_eq!(stepped.evaluations(), told.evaluations());
+
+        let population = random_population(8, 3, 4);
+        let mut stepped = DifferentialEvolution::new(population.clone(), Sphere).with_seed(4);
+        let mut told = DifferentialEvolution::new(population, External::minimize()).with_seed(4);
+        for _ in 0..21 {
+            let fitness: Vec<f64> = told.ask().iter().map(|x| Sphere.value(x)).collect();
+            told.tell(&fitness).unwrap();
+        }
+        stepped.run(20);
+        assert_eq!(stepped.population(), told.population());
+        assert_eq!(stepped.generation(), told.generation());
+    }
+
+    #[test]
+    fn test_tell_errors() {
+        let mut strategy = OnePlusOneStrategy::new(
+            vec![1.0; 2],
+            GaussianMutator::new(1.0, 0.1),
+            External::minimize(),
+        )
+        .with_seed(1);
+        assert_eq!(strategy.best_fitness(), None);
+        assert_eq!(strategy.tell(&[1.0]), Err(TellError::NothingAsked));
+
+        // The first ask also contains the starting point
+        assert_eq!(strategy.ask().len(), 2);
+        assert_eq!(
+            strategy.tell(&[1.0]),
+            Err(TellError::CountMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(strategy.tell(&[2.0, 1.0]), Ok(()));
+        assert_eq!(strategy.ask().len(), 1);
+        assert_eq!(strategy.best_fitness(), Some(1.0));
+    }
+
+    #[cfg(feature = "serde")]
+    mod checkpoint {
+        use super::*;
+        use crate::evolution_strategies::{
+            RestartFactory, RestartParameters, RestartStrategy, StagnationCriteria,
+        };
+        use crate::genetic_algorithms::{GeneticAlgorithm, SbxCrossover};
+        use serde::de::DeserializeOwned;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize)]
+        struct CmaFactory;
+
+        impl RestartFactory<CmaEs<Vec<f64>, Sphere>> for CmaFactory {
+            fn build(&mut self, parameters: RestartParameters) -> CmaEs<Vec<f64>, Sphere> {
+                CmaEs::new(vec![2.0; 3], parameters.step_size_factor, Sphere)
+                    .with_population_size(parameters.population_size)
+                    .with_seed(parameters.seed)
+            }
+        }
+
+        // Saves the strategy after 15 generations and checks that both restored
+        // copies continue exactly like the original
+        fn assert_resumes<S>(mut strategy: S) -> S
+        where
+            S: Optimizer<Vec<f64>> + Serialize + DeserializeOwned,
+        {
+            strategy.run(15);
+            let json = serde_json::to_string(&strategy).unwrap();
+            let binary = bincode::serialize(&strategy).unwrap();
+            let mut from_json: S = serde_json::from_str(&json).unwrap();
+            let mut from_binary: S = bincode::deserialize(&binary).unwrap();
+
+            strategy.run(15);
+            for restored in [&mut from_json, &mut from_binary] {
+                restored.run(15);
+                assert_eq!(restored.generation(), strategy.generation());
+                assert_eq!(restored.evaluations(), strategy.evaluations());
+                assert_eq!(restored.population(), strategy.population());
+                assert_eq!(restored.best_fitness(), strategy.best_fitness());
+            }
+            strategy
+        }
+
+        #[test]
+        fn test_checkpoint_and_resume() {
+            let start = vec![2.0; 3];
+            let population = random_population(10, 3, 7);
+
+            assert_resumes(
+                OnePlusOneStrategy::new(start.clone(), GaussianMutator::new(1.0, 0.5), Sphere)
+                    .with_seed(7),
+            );
+            assert_resumes(
+                AdaptiveOnePlusOneStrategy::new(
+                    start.clone(),
+                    GaussianMutator::new(1.0, 1.0),
+                    Sphere,
+                )
+                .with_window(5)
+                .with_seed(7),
+            );
+            assert_resumes(CmaEs::new(start, 1.0, Sphere).with_seed(7));
+            assert_resumes(
+                MuPlusLambdaStrategy::new(
+                    population.clone(),
+                    20,
+                    GaussianMutator::new(1.0, 0.1),
+                    SimpleSelector::new(5, Sphere),
+                )
+                .with_seed(7),
+            );
+            assert_resumes(
+                GeneticAlgorithm::new(
+                    population.clone(),
+                    SbxCrossover::default(),
+                    GaussianMutator::new(0.5, 0.1),
+                    SimpleSelector::new(10, Sphere),
+                )
+                .with_seed(7),
+            );
+            assert_resumes(DifferentialEvolution::new(population.clone(), Sphere).with_seed(7));
+            assert_resumes(ParticleSwarm::new(population, Sphere).with_seed(7));
+            // Improvements below 1 do not count, so the inner strategies
+            // restart within the first generations
+            let criteria = StagnationCriteria {
+                tol_fun: 1.0,
+                max_stagnation: 3,
+                ..StagnationCriteria::default()
+            };
+            let restart = assert_resumes(
+                RestartStrategy::ipop(6, CmaFactory)
+                    .with_stagnation_criteria(criteria)
+                    .with_seed(7),
+            );
+            assert!(restart.restarts() > 0);
+        }
+    }
+}
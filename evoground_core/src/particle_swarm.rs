@@ -0,0 +1,552 @@
+// Module for particle swarm optimization
+//
+// Every particle moves through the search space with a velocity that is pulled
+// towards its own best position and the best position of its neighbourhood.
+// The topology decides which particles form a neighbourhood: the global best
+// topology converges quickly, while the ring and von Neumann topologies spread
+// information slowly and keep the swarm diverse for longer. Velocities are
+// damped either by an inertia weight or by Clerc's constriction factor.
+//
+// With ask/tell the first ask returns the initial positions and every later
+// ask the positions after one move of the swarm.
+//
+// Under constraints the current positions and the personal bests are ranked
+// together once per generation. A position replaces its personal best if it
+// ranks ahead of it, and neighbourhood bests are the best ranked personal
+// bests.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::bounds::Bounds;
+use crate::constraints::{Ranking, Unconstrained};
+use crate::evaluation::Evaluated;
+use crate::genome::Genome;
+use crate::objective::{Objective, OptimizationDirection};
+use crate::optimizer::{check_tell, AskTell, Optimizer, TellError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+    // Every particle is informed by the whole swarm
+    Global,
+    // Every particle is informed by its left and right neighbour
+    Ring,
+    // Particles are placed on a toroidal grid and informed by the particles
+    // above, below, left and right of them
+    VonNeumann,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VelocityUpdate {
+    // v = w v + c1 r1 (p - x) + c2 r2 (l - x)
+    Inertia {
+        weight: f64,
+        cognitive: f64,
+        social: f64,
+    },
+    // v = chi (v + phi1 r1 (p - x) + phi2 r2 (l - x)), where chi is derived
+    // from phi = phi1 + phi2 > 4
+    Constriction {
+        cognitive: f64,
+        social: f64,
+    },
+}
+
+impl VelocityUpdate {
+    // Returns the factor applied to the old velocity, the attraction
+    // coefficients and whether the whole update is scaled by it
+    fn coefficients(&self) -> (f64, f64, f64, bool) {
+        match *self {
+            VelocityUpdate::Inertia {
+                weight,
+                cognitive,
+                social,
+            } => (weight, cognitive, social, false),
+            VelocityUpdate::Constriction { cognitive, social } => {
+                let phi = cognitive + social;
+                let chi = 2.0 / (2.0 - phi - (phi * phi - 4.0 * phi).sqrt()).abs();
+                (chi, cognitive, social, true)
+            }
+        }
+    }
+}
+
+impl Default for VelocityUpdate {
+    // The inertia weight equivalent of the standard constriction setting
+    fn default() -> Self {
+        VelocityUpdate::Inertia {
+            weight: 0.7298,
+            cognitive: 1.49618,
+            social: 1.49618,
+        }
+    }
+}
+
+// Indices of the particles informing particle `i`, including `i` itself
+fn neighbourhood(topology: Topology, i: usize, size: usize) -> Vec<usize> {
+    match topology {
+        Topology::Global => (0..size).collect(),
+        Topology::Ring => vec![(i + size - 1) % size, i, (i + 1) % size],
+        Topology::VonNeumann => {
+            // The most square grid whose columns divide the swarm size
+            let columns = (1..=size)
+                .rev()
+                .find(|c| size.is_multiple_of(*c) && c * c <= size)
+                .unwrap_or(1);
+            let rows = size / columns;
+            let (row, column) = (i / columns, i % columns);
+            vec![
+                i,
+                ((row + rows - 1) % rows) * columns + column,
+                ((row + 1) % rows) * columns + column,
+                row * columns + (column + columns - 1) % columns,
+                row * columns + (column + 1) % columns,
+            ]
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticleSwarm<G, O, K = Unconstrained> {
+    positions: Vec<G>,
+    velocities: Vec<Vec<f64>>,
+    // Fitness of the current positions
+    fitness: Vec<f64>,
+    personal_best: Vec<Evaluated<G>>,
+    // Constraint violation and rank (best is 0) of every personal best
+    personal_violations: Vec<f64>,
+    personal_ranks: Vec<usize>,
+    best: Option<Evaluated<G>>,
+    objective: O,
+    ranking: K,
+    topology: Topology,
+    velocity_update: VelocityUpdate,
+    max_velocity: Option<f64>,
+    bounds: Option<Bounds>,
+    // The points at which the current positions are evaluated
+    candidates: Vec<G>,
+    rng: ChaCha8Rng,
+    generation: usize,
+    evaluations: usize,
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>> ParticleSwarm<G, O> {
+    // All particles start at rest
+    pub fn new(initial_positions: Vec<G>, objective: O) -> Self {
+        assert!(!initial_positions.is_empty(), "The swarm must not be empty");
+        let dimension = initial_positions[0].dimension();
+        assert!(
+            initial_positions.iter().all(|x| x.dimension() == dimension),
+            "All particles must have the same dimension"
+        );
+
+        ParticleSwarm {
+            velocities: vec![vec![0.0; dimension]; initial_positions.len()],
+            positions: initial_positions,
+            fitness: Vec::new(),
+            personal_best: Vec::new(),
+            personal_violations: Vec::new(),
+            personal_ranks: Vec::new(),
+            best: None,
+            objective,
+            ranking: Unconstrained,
+            topology: Topology::Global,
+            velocity_update: VelocityUpdate::default(),
+            max_velocity: None,
+            bounds: None,
+            candidates: Vec::new(),
+            rng: ChaCha8Rng::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> ParticleSwarm<G, O, K> {
+    // Compares positions and personal bests under constraints, see
+    // `crate::constraints`
+    pub fn with_constraints<K2: Ranking<G>>(self, ranking: K2) -> ParticleSwarm<G, O, K2> {
+        ParticleSwarm {
+            positions: self.positions,
+            velocities: self.velocities,
+            fitness: self.fitness,
+            personal_best: self.personal_best,
+            personal_violations: self.personal_violations,
+            personal_ranks: self.personal_ranks,
+            best: self.best,
+            objective: self.objective,
+            ranking,
+            topology: self.topology,
+            velocity_update: self.velocity_update,
+            max_velocity: self.max_velocity,
+            bounds: self.bounds,
+            candidates: self.candidates,
+            rng: self.rng,
+            generation: self.generation,
+            evaluations: self.evaluations,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_velocity_update(mut self, velocity_update: VelocityUpdate) -> Self {
+        if let VelocityUpdate::Constriction { cognitive, social } = velocity_update {
+            assert!(
+                cognitive + social > 4.0,
+                "The constriction factor needs phi1 + phi2 > 4"
+            );
+        }
+        self.velocity_update = velocity_update;
+        self
+    }
+
+    // Limits every velocity component to [-max_velocity, max_velocity]
+    pub fn with_max_velocity(mut self, max_velocity: f64) -> Self {
+        assert!(max_velocity > 0.0, "The maximum velocity must be positive");
+        self.max_velocity = Some(max_velocity);
+        self
+    }
+
+    // Particles leaving the box are repaired (or penalized) according to the bounds
+    pub fn with_bounds(mut self, bounds: Bounds) -> Self {
+        assert_eq!(
+            bounds.dimension(),
+            self.positions[0].dimension(),
+            "The bounds do not match the dimension of the search space"
+        );
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn step(&mut self) {
+        self.step_observed(&mut |_, _| {});
+    }
+
+    // Like `step`, and reports every evaluated candidate with its fitness
+    pub fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        if self.personal_best.is_empty() {
+            self.evaluate_candidates(on_evaluation);
+        }
+        self.evaluate_candidates(on_evaluation);
+    }
+
+    fn evaluate_candidates(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        self.ask();
+        let fitness = self.objective.evaluate_batch(&self.candidates);
+        for (position, fitness) in self.candidates.iter().zip(&fitness) {
+            on_evaluation(position, *fitness);
+        }
+        self.tell(&fitness).unwrap();
+    }
+
+    pub fn ask(&mut self) -> &[G] {
+        if !self.candidates.is_empty() {
+            return &self.candidates;
+        }
+        if self.personal_best.is_empty() {
+            if let Some(bounds) = &self.bounds {
+                for position in self.positions.iter_mut() {
+                    bounds.repair(position, &mut self.rng);
+                }
+            }
+        } else {
+            self.move_particles();
+        }
+
+        self.candidates = match &self.bounds {
+            Some(bounds) => self
+                .positions
+                .iter()
+                .map(|position| bounds.evaluation_point(position))
+                .collect(),
+            None => self.positions.clone(),
+        };
+        &self.candidates
+    }
+
+    // Telling the fitness of the initial positions does not count as a generation
+    pub fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        check_tell(&self.candidates, fitness)?;
+        self.candidates.clear();
+        self.evaluations += fitness.len();
+
+        let direction = self.objective.direction();
+        self.fitness = match &self.bounds {
+            Some(bounds) => self
+                .positions
+                .iter()
+                .zip(fitness)
+                .map(|(position, fitness)| bounds.penalize(*fitness, position, direction))
+                .collect(),
+            None => fitness.to_vec(),
+        };
+        let violations = self.ranking.violations(&self.positions);
+
+        let n = self.positions.len();
+        if self.personal_best.is_empty() {
+            self.personal_best = self
+                .positions
+                .iter()
+                .zip(&self.fitness)
+                .map(|(position, fitness)| Evaluated::new(position.clone(), *fitness))
+                .collect();
+            self.personal_violations = violations;
+            let order = self.ranking.rank(
+                &self.fitness,
+                &self.personal_violations,
+                direction,
+                &mut self.rng,
+            );
+            self.personal_ranks = vec![0; n];
+            for (rank, index) in order.into_iter().enumerate() {
+                self.personal_ranks[index] = rank;
+            }
+        } else {
+            // Personal bests come first, so that a position has to be
+            // strictly better to replace its personal best
+            let personal_fitness: Vec<f64> = self.personal_best.iter().map(|b| b.fitness).collect();
+            let order = self.ranking.rank(
+                &[personal_fitness.as_slice(), &self.fitness].concat(),
+                &[self.personal_violations.as_slice(), &violations].concat(),
+                direction,
+                &mut self.rng,
+            );
+            let mut ranks = vec![0; 2 * n];
+            for (rank, index) in order.into_iter().enumerate() {
+                ranks[index] = rank;
+            }
+            for i in 0..n {
+                if ranks[n + i] < ranks[i] {
+                    self.personal_best[i] =
+                        Evaluated::new(self.positions[i].clone(), self.fitness[i]);
+                    self.personal_violations[i] = violations[i];
+                }
+                self.personal_ranks[i] = ranks[i].min(ranks[n + i]);
+            }
+            self.generation += 1;
+        }
+
+        // The best personal best is the best position found so far
+        let best = (0..n).min_by_key(|i| self.personal_ranks[*i]).unwrap();
+        self.best = Some(self.personal_best[best].clone());
+        Ok(())
+    }
+
+    fn move_particles(&mut self) {
+        // Neighbourhood bests are taken from the swarm at the start of the generation
+        let size = self.positions.len();
+        let informants: Vec<usize> = (0..size)
+            .map(|i| {
+                neighbourhood(self.topology, i, size)
+                    .into_iter()
+                    .min_by_key(|j| self.personal_ranks[*j])
+                    .unwrap()
+            })
+            .collect();
+
+        let (factor, cognitive, social, constricted) = self.velocity_update.coefficients();
+        for (i, informant) in informants.into_iter().enumerate() {
+            let previous = self.positions[i].genes().to_vec();
+            let own_best = self.personal_best[i].genome.genes();
+            let local_best = self.personal_best[informant].genome.genes();
+
+            for (j, v) in self.velocities[i].iter_mut().enumerate() {
+                let pull = cognitive * self.rng.gen::<f64>() * (own_best[j] - previous[j])
+                    + social * self.rng.gen::<f64>() * (local_best[j] - previous[j]);
+                *v = if constricted {
+                    factor * (*v + pull)
+                } else {
+                    factor * *v + pull
+                };
+                if let Some(max_velocity) = self.max_velocity {
+                    *v = v.clamp(-max_velocity, max_velocity);
+                }
+            }
+
+            for (x, v) in self.positions[i]
+                .genes_mut()
+                .iter_mut()
+                .zip(&self.velocities[i])
+            {
+                *x += v;
+            }
+            if let Some(bounds) = &self.bounds {
+                // The velocity becomes the displacement that actually happened
+                bounds.repair(&mut self.positions[i], &mut self.rng);
+                for ((v, x), p) in self.velocities[i]
+                    .iter_mut()
+                    .zip(self.positions[i].genes())
+                    .zip(&previous)
+                {
+                    *v = x - p;
+                }
+            }
+        }
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    // The current positions of all particles
+    pub fn population(&self) -> &[G] {
+        &self.positions
+    }
+
+    pub fn velocities(&self) -> &[Vec<f64>] {
+        &self.velocities
+    }
+
+    // The best position found so far, or the first particle before the first generation
+    pub fn best_individual(&self) -> &G {
+        self.best
+            .as_ref()
+            .map_or(&self.positions[0], |best| &best.genome)
+    }
+
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best.as_ref().map(|best| best.fitness)
+    }
+
+    pub fn direction(&self) -> OptimizationDirection {
+        self.objective.direction()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> Optimizer<G>
+    for ParticleSwarm<G, O, K>
+{
+    fn step(&mut self) {
+        ParticleSwarm::step(self)
+    }
+
+    fn step_observed(&mut self, on_evaluation: &mut dyn FnMut(&G, f64)) {
+        ParticleSwarm::step_observed(self, on_evaluation)
+    }
+
+    fn best_individual(&self) -> &G {
+        ParticleSwarm::best_individual(self)
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        ParticleSwarm::best_fitness(self)
+    }
+
+    fn evaluations(&self) -> usize {
+        ParticleSwarm::evaluations(self)
+    }
+
+    fn generation(&self) -> usize {
+        ParticleSwarm::generation(self)
+    }
+
+    fn population(&self) -> &[G] {
+        ParticleSwarm::population(self)
+    }
+
+    fn population_fitness(&self) -> &[f64] {
+        &self.fitness
+    }
+
+    fn direction(&self) -> OptimizationDirection {
+        ParticleSwarm::direction(self)
+    }
+}
+
+impl<G: Genome<Gene = f64>, O: Objective<G>, K: Ranking<G>> AskTell<G> for ParticleSwarm<G, O, K> {
+    fn ask(&mut self) -> &[G] {
+        ParticleSwarm::ask(self)
+    }
+
+    fn tell(&mut self, fitness: &[f64]) -> Result<(), TellError> {
+        ParticleSwarm::tell(self, fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::Sphere;
+    use crate::testing::random_population;
+
+    #[test]
+    fn test_neighbourhoods() {
+        assert_eq!(neighbourhood(Topology::Ring, 0, 5), vec![4, 0, 1]);
+        // A grid with 4 rows and 3 columns, particle 5 sits in row 1, column 2
+        let mut neighbours = neighbourhood(Topology::VonNeumann, 5, 12);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![2, 3, 4, 5, 8]);
+        assert_eq!(neighbourhood(Topology::Global, 2, 3), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_topologies_and_velocity_updates_solve_sphere() {
+        for topology in [Topology::Global, Topology::Ring, Topology::VonNeumann] {
+            for velocity_update in [
+                VelocityUpdate::default(),
+                VelocityUpdate::Constriction {
+                    cognitive: 2.05,
+                    social: 2.05,
+                },
+            ] {
+                let mut pso = ParticleSwarm::new(random_population(20, 5, 4), Sphere)
+                    .with_topology(topology)
+                    .with_velocity_update(velocity_update)
+                    .with_seed(4);
+                pso.run(500);
+
+                assert!(
+                    pso.best_fitness().unwrap() < 1e-8,
+                    "{:?} with {:?} did not converge: {:?}",
+                    topology,
+                    velocity_update,
+                    pso.best_fitness()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_velocity_clamping_and_bounds() {
+        let bounds = Bounds::uniform(2, -1.0, 1.0);
+        let objective = |x: &Vec<f64>| -> f64 {
+            assert!(x.iter().all(|v| v.abs() <= 1.0));
+            x[0] + x[1]
+        };
+
+        let mut pso = ParticleSwarm::new(random_population(10, 2, 5), objective)
+            .with_bounds(bounds.clone())
+            .with_max_velocity(0.5)
+            .with_seed(5);
+        pso.run(100);
+
+        assert!(pso
+            .velocities()
+            .iter()
+            .flatten()
+            .all(|v| v.abs() <= 0.5 + 1e-12));
+        assert!(pso.population().iter().all(|x| bounds.contains(x)));
+        assert!(pso.best_individual().iter().all(|v| (v - 1.0).abs() < 1e-6));
+        assert_eq!(pso.evaluations(), 10 * 101);
+    }
+}
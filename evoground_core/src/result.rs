@@ -0,0 +1,132 @@
+// Outcome of a run
+//
+// `Optimizer::run_until` returns the best individual, the budget that was used
+// and the reason the run stopped. `Optimizer::run_with_history` additionally
+// records statistics of the population fitness after every generation, e.g. to
+// plot convergence curves.
+
+use std::time::Duration;
+
+use crate::objective::OptimizationDirection;
+use crate::termination::TerminationReason;
+
+#[derive(Debug, Clone)]
+pub struct RunResult<G> {
+    pub best_individual: G,
+    // None if the optimizer does not know the fitness of its best individual
+    pub best_fitness: Option<f64>,
+    pub evaluations: usize,
+    pub generations: usize,
+    pub termination: TerminationReason,
+    pub elapsed: Duration,
+    // One entry per generation of the run, if requested
+    pub history: Option<Vec<GenerationStats>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub evaluations: usize,
+    // Best, mean, worst and standard deviation of the population fitness
+    pub best: f64,
+    pub mean: f64,
+    pub worst: f64,
+    pub std: f64,
+    pub step_size: Option<f64>,
+}
+
+impl GenerationStats {
+    // None if the fitness of the population is unknown
+    pub fn new(
+        generation: usize,
+        evaluations: usize,
+        fitness: &[f64],
+        direction: OptimizationDirection,
+        step_size: Option<f64>,
+    ) -> Option<Self> {
+        let first = *fitness.first()?;
+        let (mut best, mut worst) = (first, first);
+        for f in fitness {
+            if direction.is_better(*f, best) {
+                best = *f;
+            }
+            if direction.is_better(worst, *f) {
+                worst = *f;
+            }
+        }
+        let n = fitness.len() as f64;
+        let mean = fitness.iter().sum::<f64>() / n;
+        let variance = fitness.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / n;
+
+        Some(GenerationStats {
+            generation,
+            evaluations,
+            best,
+            mean,
+            worst,
+            std: variance.sqrt(),
+            step_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere};
+    use crate::evolution_strategies::CmaEs;
+    use crate::optimizer::Optimizer;
+    use crate::termination::MaxGenerations;
+
+    #[test]
+    fn test_generation_stats() {
+        let fitness = [3.0, 1.0, 2.0, 6.0];
+
+        let stats =
+            GenerationStats::new(4, 40, &fitness, OptimizationDirection::Minimize, None).unwrap();
+        assert_eq!((stats.best, stats.worst), (1.0, 6.0));
+        assert_eq!(stats.mean, 3.0);
+        assert!((stats.std - 3.5f64.sqrt()).abs() < 1e-12);
+
+        let stats =
+            GenerationStats::new(4, 40, &fitness, OptimizationDirection::Maximize, Some(0.5))
+                .unwrap();
+        assert_eq!((stats.best, stats.worst), (6.0, 1.0));
+        assert_eq!(stats.step_size, Some(0.5));
+
+        assert!(GenerationStats::new(0, 0, &[], OptimizationDirection::Minimize, None).is_none());
+    }
+
+    #[test]
+    fn test_run_with_history() {
+        let objective = Sphere;
+        let mut strategy = CmaEs::new(vec![2.0; 3], 1.0, objective).with_seed(5);
+
+        let result = strategy.run_with_history(&mut MaxGenerations::new(50));
+        assert_eq!(result.termination, TerminationReason::MaxGenerations);
+        assert_eq!(result.generations, 50);
+        assert_eq!(result.evaluations, strategy.evaluations());
+        assert_eq!(
+            result.best_fitness,
+            Some(Sphere.value(&result.best_individual))
+        );
+
+        let history = result.history.unwrap();
+        assert_eq!(history.len(), 50);
+        for (i, stats) in history.iter().enumerate() {
+            assert_eq!(stats.generation, i + 1);
+            assert!(stats.best <= stats.mean && stats.mean <= stats.worst);
+            assert!(stats.step_size.is_some());
+        }
+        // The best fitness so far is the best of all recorded generations
+        let best = history
+            .iter()
+            .map(|stats| stats.best)
+            .fold(f64::INFINITY, f64::min);
+        assert_eq!(result.best_fitness, Some(best));
+
+        let result = strategy.run_until(&mut MaxGenerations::new(60));
+        assert_eq!(result.generations, 60);
+        assert!(result.history.is_none());
+    }
+}
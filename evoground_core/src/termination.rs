@@ -0,0 +1,418 @@
+// Termination criteria
+//
+// A criterion looks at the state of an optimizer before every generation and
+// decides whether the run is over. Criteria are combined with `or`, which
+// stops as soon as one of them fires, and `and`, which stops once all of them
+// have fired. The result of `Optimizer::run_until` reports the criterion that
+// ended the run.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::genome::Genome;
+use crate::objective::OptimizationDirection;
+
+// Snapshot of an optimizer handed to the criteria and observers
+pub struct Status<'a, G> {
+    pub generation: usize,
+    pub evaluations: usize,
+    pub best_fitness: Option<f64>,
+    pub step_size: Option<f64>,
+    pub population: &'a [G],
+    pub population_fitness: &'a [f64],
+    pub direction: OptimizationDirection,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerminationReason {
+    MaxGenerations,
+    MaxEvaluations,
+    MaxTime,
+    TargetFitness,
+    Stagnation,
+    StepSize,
+    PopulationSpread,
+    // An observer asked to stop
+    Observer,
+    // Every criterion of an `and` combination fired
+    All(Vec<TerminationReason>),
+}
+
+impl fmt::Display for TerminationReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TerminationReason::MaxGenerations => write!(f, "maximum number of generations"),
+            TerminationReason::MaxEvaluations => write!(f, "maximum number of evaluations"),
+            TerminationReason::MaxTime => write!(f, "time limit"),
+            TerminationReason::TargetFitness => write!(f, "target fitness reached"),
+            TerminationReason::Stagnation => write!(f, "fitness stagnation"),
+            TerminationReason::StepSize => write!(f, "step size tolerance"),
+            TerminationReason::PopulationSpread => write!(f, "population spread tolerance"),
+            TerminationReason::Observer => write!(f, "stopped by an observer"),
+            TerminationReason::All(reasons) => {
+                for (i, reason) in reasons.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " and ")?;
+                    }
+                    write!(f, "{}", reason)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+pub trait Termination<G> {
+    // Called once before the first check of a run, resets any state
+    fn start(&mut self) {}
+
+    // The reason to stop, or None to keep going
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason>;
+}
+
+// The combinators live in their own trait so that chaining criteria does not
+// depend on the genome type. Custom criteria opt in with an empty impl.
+pub trait Combine: Sized {
+    fn or<T>(self, other: T) -> Any<Self, T> {
+        Any(self, other)
+    }
+
+    fn and<T>(self, other: T) -> All<Self, T> {
+        All(self, other)
+    }
+}
+
+impl<A, B> Combine for Any<A, B> {}
+impl<A, B> Combine for All<A, B> {}
+impl Combine for MaxGenerations {}
+impl Combine for MaxEvaluations {}
+impl Combine for MaxTime {}
+impl Combine for TargetFitness {}
+impl Combine for Stagnation {}
+impl Combine for StepSizeTolerance {}
+impl Combine for PopulationSpread {}
+
+// Fires as soon as one of the criteria fires. Both criteria are checked every
+// time so that stateful ones keep track of every generation.
+pub struct Any<A, B>(A, B);
+
+impl<G, A: Termination<G>, B: Termination<G>> Termination<G> for Any<A, B> {
+    fn start(&mut self) {
+        self.0.start();
+        self.1.start();
+    }
+
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        let first = self.0.check(status);
+        let second = self.1.check(status);
+        first.or(second)
+    }
+}
+
+// Fires once both criteria fire in the same check
+pub struct All<A, B>(A, B);
+
+impl<G, A: Termination<G>, B: Termination<G>> Termination<G> for All<A, B> {
+    fn start(&mut self) {
+        self.0.start();
+        self.1.start();
+    }
+
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        let first = self.0.check(status)?;
+        let second = self.1.check(status)?;
+        let mut reasons = Vec::new();
+        for reason in [first, second] {
+            match reason {
+                TerminationReason::All(nested) => reasons.extend(nested),
+                reason => reasons.push(reason),
+            }
+        }
+        Some(TerminationReason::All(reasons))
+    }
+}
+
+pub struct MaxGenerations {
+    generations: usize,
+}
+
+impl MaxGenerations {
+    pub fn new(generations: usize) -> Self {
+        MaxGenerations { generations }
+    }
+}
+
+impl<G> Termination<G> for MaxGenerations {
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        (status.generation >= self.generations).then_some(TerminationReason::MaxGenerations)
+    }
+}
+
+// Stops once the budget is used up. Strategies evaluate whole generations, so
+// the last generation may exceed it.
+pub struct MaxEvaluations {
+    evaluations: usize,
+}
+
+impl MaxEvaluations {
+    pub fn new(evaluations: usize) -> Self {
+        MaxEvaluations { evaluations }
+    }
+}
+
+impl<G> Termination<G> for MaxEvaluations {
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        (status.evaluations >= self.evaluations).then_some(TerminationReason::MaxEvaluations)
+    }
+}
+
+// Wall-clock time since the start of the run
+pub struct MaxTime {
+    limit: Duration,
+    started: Option<Instant>,
+}
+
+impl MaxTime {
+    pub fn new(limit: Duration) -> Self {
+        MaxTime {
+            limit,
+            started: None,
+        }
+    }
+}
+
+impl<G> Termination<G> for MaxTime {
+    fn start(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    fn check(&mut self, _status: &Status<G>) -> Option<TerminationReason> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        (started.elapsed() >= self.limit).then_some(TerminationReason::MaxTime)
+    }
+}
+
+// Stops once the best fitness is at least as good as the target, in the
+// direction of the optimizer
+pub struct TargetFitness {
+    target: f64,
+}
+
+impl TargetFitness {
+    pub fn new(target: f64) -> Self {
+        TargetFitness { target }
+    }
+}
+
+impl<G> Termination<G> for TargetFitness {
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        status
+            .best_fitness
+            .filter(|fitness| !status.direction.is_better(self.target, *fitness))
+            .map(|_| TerminationReason::TargetFitness)
+    }
+}
+
+// Stops after `generations` generations in which the best fitness changed by
+// no more than `tolerance`. Optimizers that do not report their best fitness
+// never stagnate.
+pub struct Stagnation {
+    generations: usize,
+    tolerance: f64,
+    best: Option<f64>,
+    stagnant: usize,
+}
+
+impl Stagnation {
+    pub fn new(generations: usize, tolerance: f64) -> Self {
+        assert!(generations > 0, "Stagnation needs at least one generation");
+        Stagnation {
+            generations,
+            tolerance,
+            best: None,
+            stagnant: 0,
+        }
+    }
+}
+
+impl<G> Termination<G> for Stagnation {
+    fn start(&mut self) {
+        self.best = None;
+        self.stagnant = 0;
+    }
+
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        let fitness = status.best_fitness?;
+        match self.best {
+            Some(best) if (fitness - best).abs() <= self.tolerance => self.stagnant += 1,
+            _ => {
+                self.best = Some(fitness);
+                self.stagnant = 0;
+            }
+        }
+        (self.stagnant >= self.generations).then_some(TerminationReason::Stagnation)
+    }
+}
+
+// Stops once the global step size drops below the tolerance. Optimizers
+// without a step size never fire it.
+pub struct StepSizeTolerance {
+    tolerance: f64,
+}
+
+impl StepSizeTolerance {
+    pub fn new(tolerance: f64) -> Self {
+        StepSizeTolerance { tolerance }
+    }
+}
+
+impl<G> Termination<G> for StepSizeTolerance {
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        status
+            .step_size
+            .filter(|sigma| *sigma < self.tolerance)
+            .map(|_| TerminationReason::StepSize)
+    }
+}
+
+// Stops once the population fits into a box whose widest side is below the
+// tolerance. A single individual has no spread, so strategies like (1+1)
+// never fire it and should use `StepSizeTolerance` instead.
+pub struct PopulationSpread {
+    tolerance: f64,
+}
+
+impl PopulationSpread {
+    pub fn new(tolerance: f64) -> Self {
+        PopulationSpread { tolerance }
+    }
+}
+
+impl<G: Genome<Gene = f64>> Termination<G> for PopulationSpread {
+    fn check(&mut self, status: &Status<G>) -> Option<TerminationReason> {
+        if status.population.len() < 2 {
+            return None;
+        }
+        let first = &status.population[0];
+        let spread = (0..first.dimension())
+            .map(|j| {
+                let values = status.population.iter().map(|x| x.genes()[j]);
+                let min = values.clone().fold(f64::INFINITY, f64::min);
+                let max = values.fold(f64::NEG_INFINITY, f64::max);
+                max - min
+            })
+            .fold(0.0, f64::max);
+        (spread < self.tolerance).then_some(TerminationReason::PopulationSpread)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{Benchmark, Sphere};
+    use crate::differential_evolution::DifferentialEvolution;
+    use crate::evolution_strategies::{CmaEs, GaussianMutator, OnePlusOneStrategy};
+    use crate::objective::{Maximize, Minimize};
+    use crate::optimizer::Optimizer;
+
+    #[test]
+    fn test_target_fitness_or_max_generations() {
+        let objective = Sphere;
+        let mut strategy = CmaEs::new(vec![2.0; 3], 1.0, objective).with_seed(1);
+        let mut termination = TargetFitness::new(1e-6).or(MaxGenerations::new(1000));
+
+        let reason = strategy.run_until(&mut termination).termination;
+        assert_eq!(reason, TerminationReason::TargetFitness);
+        assert!(strategy.best_fitness().unwrap() <= 1e-6);
+        assert!(strategy.generation() < 1000);
+
+        let mut strategy = CmaEs::new(vec![2.0; 3], 1.0, objective).with_seed(1);
+        let mut termination = TargetFitness::new(-1.0)
+            .or(MaxGenerations::new(20))
+            .or(MaxTime::new(Duration::from_secs(60)));
+        assert_eq!(
+            strategy.run_until(&mut termination).termination,
+            TerminationReason::MaxGenerations
+        );
+        assert_eq!(strategy.generation(), 20);
+    }
+
+    #[test]
+    fn test_all_waits_for_every_criterion() {
+        let objective = Sphere;
+        let mut strategy = CmaEs::new(vec![2.0; 3], 1.0, objective).with_seed(2);
+        let mut termination = MaxGenerations::new(5).and(MaxEvaluations::new(100));
+
+        let reason = strategy.run_until(&mut termination).termination;
+        assert_eq!(
+            reason,
+            TerminationReason::All(vec![
+                TerminationReason::MaxGenerations,
+                TerminationReason::MaxEvaluations
+            ])
+        );
+        assert!(strategy.generation() >= 5);
+        assert!(strategy.evaluations() >= 100);
+        assert_eq!(
+            reason.to_string(),
+            "maximum number of generations and maximum number of evaluations"
+        );
+    }
+
+    #[test]
+    fn test_convergence_criteria() {
+        let objective = Sphere;
+        let mut strategy = CmaEs::new(vec![2.0; 3], 1.0, objective).with_seed(3);
+        let mut termination = StepSizeTolerance::new(1e-8).or(MaxGenerations::new(5000));
+        assert_eq!(
+            strategy.run_until(&mut termination).termination,
+            TerminationReason::StepSize
+        );
+        assert!(strategy.sigma() < 1e-8);
+
+        // A flat objective never improves
+        let population: Vec<Vec<f64>> = (0..6).map(|i| vec![i as f64; 2]).collect();
+        let mut de =
+            DifferentialEvolution::new(population, Minimize(|_: &Vec<f64>| 1.0)).with_seed(2);
+        let mut termination = Stagnation::new(10, 0.0).or(MaxGenerations::new(100));
+        assert_eq!(
+            de.run_until(&mut termination).termination,
+            TerminationReason::Stagnation
+        );
+        // The best fitness is first known after one generation
+        assert_eq!(de.generation(), 11);
+
+        let population: Vec<Vec<f64>> = (0..10).map(|i| vec![i as f64 - 5.0; 2]).collect();
+        let mut de = DifferentialEvolution::new(population, objective).with_seed(3);
+        let mut termination = PopulationSpread::new(1e-3).or(MaxGenerations::new(1000));
+        assert_eq!(
+            de.run_until(&mut termination).termination,
+            TerminationReason::PopulationSpread
+        );
+    }
+
+    #[test]
+    fn test_target_fitness_follows_direction() {
+        let objective = Maximize(|x: &Vec<f64>| -Sphere.value(x));
+        let mut strategy = CmaEs::new(vec![2.0; 3], 1.0, objective).with_seed(4);
+        let mut termination = TargetFitness::new(-1e-6).or(MaxGenerations::new(1000));
+
+        let reason = strategy.run_until(&mut termination).termination;
+        assert_eq!(reason, TerminationReason::TargetFitness);
+        assert!(strategy.best_fitness().unwrap() >= -1e-6);
+    }
+
+    #[test]
+    fn test_population_spread_ignores_single_individual() {
+        let objective = Sphere;
+        let mut strategy =
+            OnePlusOneStrategy::new(vec![2.0; 3], GaussianMutator::new(1.0, 0.1), objective)
+                .with_seed(5);
+        let mut termination = PopulationSpread::new(1e-3).or(MaxGenerations::new(20));
+        assert_eq!(
+            strategy.run_until(&mut termination).termination,
+            TerminationReason::MaxGenerations
+        );
+        assert_eq!(strategy.generation(), 20);
+    }
+}
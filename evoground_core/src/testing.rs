@@ -0,0 +1,13 @@
+// Helpers shared by the tests of several modules
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::bounds::Bounds;
+
+// `size` points drawn uniformly from [-5, 5]^dimension
+pub(crate) fn random_population(size: usize, dimension: usize, seed: u64) -> Vec<Vec<f64>> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let bounds = Bounds::uniform(dimension, -5.0, 5.0);
+    (0..size).map(|_| bounds.sample(&mut rng)).collect()
+}